@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use imstr::ImString;
+
+fn compare(c: &mut Criterion) {
+    let length = 64 * 1024;
+    let base = "a".repeat(length);
+    let string = ImString::from(base.as_str());
+
+    let last_byte = ImString::from(format!("{}b", &base[..length - 1]));
+    c.bench_function("eq differ in last byte", |b| {
+        b.iter(|| black_box(&string) == black_box(&last_byte))
+    });
+
+    let shorter = ImString::from(&base[..length - 1]);
+    c.bench_function("eq differ in length", |b| {
+        b.iter(|| black_box(&string) == black_box(&shorter))
+    });
+
+    let sliced = ImString::from(format!("x{base}")).slice(1..);
+    c.bench_function("eq sliced equal", |b| {
+        b.iter(|| black_box(&string) == black_box(&sliced))
+    });
+
+    c.bench_function("eq str equal", |b| {
+        b.iter(|| black_box(string.as_str()) == black_box(base.as_str()))
+    });
+}
+
+criterion_group!(benches, compare);
+criterion_main!(benches);
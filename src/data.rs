@@ -1,6 +1,8 @@
 pub use std::boxed::Box;
+use std::ops::Deref;
 pub use std::rc::Rc;
 pub use std::sync::Arc;
+use std::sync::OnceLock;
 
 /// Set of common operations on shared data.
 ///
@@ -12,6 +14,11 @@ pub use std::sync::Arc;
 /// The `Data` trait is generic over the type of the inner value of the shared data, `T`.
 /// Implementers of this trait must provide methods to immutable access, and may provide methods to
 /// mutably access the data.
+///
+/// Storage types which do not always keep their data in a `T`, such as [`Static`] and [`Inline`],
+/// can additionally override the provided methods which access the data through its borrowed
+/// form, such as [`get_borrowed()`](Data::get_borrowed). Storage types which keep their data in a
+/// `T` do not need to override them.
 pub trait Data<T>: Clone {
     /// Create some new data.
     ///
@@ -105,6 +112,184 @@ pub trait Data<T>: Clone {
     /// assert_eq!(data.get(), &16);
     /// ```
     fn get_mut(&mut self) -> Option<&mut T>;
+
+    /// Returns the number of references to the shared data.
+    ///
+    /// For storage types which do not share data, such as [`Box<T>`] or [`Cloned<T>`], this is
+    /// always 1. The default implementation returns 1, so storage types which do share data
+    /// should override it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::Data;
+    /// use std::sync::Arc;
+    ///
+    /// let data = Arc::new(15);
+    /// assert_eq!(Data::ref_count(&data), 1);
+    /// let clone = data.clone();
+    /// assert_eq!(Data::ref_count(&data), 2);
+    /// ```
+    fn ref_count(&self) -> usize {
+        1
+    }
+
+    /// Returns `true` if there are no other references to the shared data.
+    ///
+    /// For storage types which do not share data, this is always `true`. Note that this does not
+    /// guarantee that [`Data::get_mut`] returns a mutable reference: an [`Arc`] or [`Rc`] with
+    /// outstanding [`Weak`](std::sync::Weak) references is unique, but cannot be mutated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::Data;
+    /// use std::rc::Rc;
+    ///
+    /// let data = Rc::new(15);
+    /// assert!(Data::is_unique(&data));
+    /// let clone = data.clone();
+    /// assert!(!Data::is_unique(&data));
+    /// ```
+    fn is_unique(&self) -> bool {
+        self.ref_count() == 1
+    }
+
+    /// Creates some new data from the borrowed form of a value, such as a `&str` for a `String`.
+    ///
+    /// The default implementation calls [`Data::new`] with an owned copy of the value. Storage
+    /// types that can hold small values without allocating override it to avoid the copy, see
+    /// [`Inline`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::Data;
+    /// use std::sync::Arc;
+    ///
+    /// let data = <Arc<String> as Data<String>>::new_borrowed("hello");
+    /// assert_eq!(*data, "hello");
+    /// ```
+    fn new_borrowed(value: &T::Target) -> Self
+    where
+        T: Deref,
+        T::Target: ToOwned<Owned = T>,
+    {
+        Self::new(value.to_owned())
+    }
+
+    /// Returns an immutable reference to the borrowed form of the shared data, such as a `&str`
+    /// for a `String`.
+    ///
+    /// The default implementation dereferences the value returned by [`Data::get`]. Storage types
+    /// which do not always keep their data in a `T`, such as [`Static`] or [`Inline`], override it
+    /// to return the data without creating a `T` first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Static};
+    /// use std::sync::Arc;
+    ///
+    /// let data = Arc::new(String::from("hello"));
+    /// assert_eq!(data.get_borrowed(), "hello");
+    /// let data = Static::from_static("hello");
+    /// assert_eq!(data.get_borrowed(), "hello");
+    /// ```
+    fn get_borrowed(&self) -> &T::Target
+    where
+        T: Deref + 'static,
+    {
+        self.get()
+    }
+
+    /// Returns an immutable reference to the `T` holding the shared data, if there is one.
+    ///
+    /// The default implementation returns the value returned by [`Data::get`]. Storage types which
+    /// do not always keep their data in a `T`, such as [`Static`] or [`Inline`], return `None`
+    /// when it is not. This is used to determine the capacity and the heap usage of the data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Static};
+    /// use std::sync::Arc;
+    ///
+    /// let data = Arc::new(String::with_capacity(16));
+    /// assert_eq!(data.get_owned().unwrap().capacity(), 16);
+    /// let data = Static::from_static("hello");
+    /// assert_eq!(data.get_owned(), None);
+    /// ```
+    fn get_owned(&self) -> Option<&T> {
+        Some(self.get())
+    }
+
+    /// Returns a mutable reference to the borrowed form of the shared data, if the storage allows
+    /// mutating it without going through [`get_mut()`](Data::get_mut).
+    ///
+    /// Storage types which keep small values inline, such as [`Inline`], never share them and can
+    /// return a mutable reference to them even though they are not kept in a `T`. The default
+    /// implementation returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Inline};
+    ///
+    /// let mut data = <Inline as Data<String>>::new_borrowed("tiny");
+    /// data.get_mut_borrowed().unwrap().make_ascii_uppercase();
+    /// assert_eq!(data.get_borrowed(), "TINY");
+    /// ```
+    fn get_mut_borrowed(&mut self) -> Option<&mut T::Target>
+    where
+        T: Deref,
+    {
+        None
+    }
+
+    /// Returns the number of elements the storage can hold without a `T`.
+    ///
+    /// Storage types which keep small values inline, such as [`Inline`], return the size of their
+    /// inline buffer while the data is stored in it. This is used to determine the capacity of
+    /// data for which [`get_owned()`](Data::get_owned) returns `None`. The default implementation
+    /// returns 0.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Inline};
+    ///
+    /// let data = <Inline<8> as Data<String>>::new_borrowed("tiny");
+    /// assert_eq!(data.inline_capacity(), 8);
+    /// ```
+    fn inline_capacity(&self) -> usize {
+        0
+    }
+
+    /// Replaces everything after the first `len` elements of the data with `value` in place,
+    /// without going through [`get_mut()`](Data::get_mut).
+    ///
+    /// Returns `false` without modifying the data if this is not possible. Storage types which
+    /// keep small values inline, such as [`Inline`], use this to append to them without copying
+    /// them into a `T`. The default implementation returns `false`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Inline};
+    ///
+    /// let mut data = <Inline<8> as Data<String>>::new_borrowed("tiny");
+    /// assert!(data.try_append(3, "e"));
+    /// assert_eq!(data.get_borrowed(), "tine");
+    /// assert!(!data.try_append(4, " string"));
+    /// ```
+    fn try_append(&mut self, len: usize, value: &T::Target) -> bool
+    where
+        T: Deref,
+    {
+        let _ = (len, value);
+        false
+    }
 }
 
 impl<T> Data<T> for Arc<T> {
@@ -113,12 +298,16 @@ impl<T> Data<T> for Arc<T> {
     }
 
     fn get(&self) -> &T {
-        &self
+        self
     }
 
     fn get_mut(&mut self) -> Option<&mut T> {
         Arc::get_mut(self)
     }
+
+    fn ref_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
 }
 
 impl<T> Data<T> for Rc<T> {
@@ -127,12 +316,16 @@ impl<T> Data<T> for Rc<T> {
     }
 
     fn get(&self) -> &T {
-        &self
+        self
     }
 
     fn get_mut(&mut self) -> Option<&mut T> {
         Rc::get_mut(self)
     }
+
+    fn ref_count(&self) -> usize {
+        Rc::strong_count(self)
+    }
 }
 
 impl<T: Clone> Data<T> for Box<T> {
@@ -141,7 +334,7 @@ impl<T: Clone> Data<T> for Box<T> {
     }
 
     fn get(&self) -> &T {
-        &self
+        self
     }
 
     fn get_mut(&mut self) -> Option<&mut T> {
@@ -150,6 +343,13 @@ impl<T: Clone> Data<T> for Box<T> {
 }
 
 /// Container for data which is not actually shared, but is cloned.
+///
+/// Every clone of a `Cloned<T>` is a deep copy of the inner value, so the data is never shared and
+/// [`get_mut()`](Data::get_mut) always succeeds. When used as storage for an
+/// [`ImString`](crate::string::ImString), this means that cloning or slicing copies the entire
+/// backing string, and mutations always happen in-place on that copy. The observable contents of
+/// the string are the same as with shared storage, but the backing string (and therefore its
+/// [`capacity()`](crate::string::ImString::capacity)) may differ.
 #[derive(Clone)]
 pub struct Cloned<T>(T);
 
@@ -167,6 +367,269 @@ impl<T: Clone> Data<T> for Cloned<T> {
     }
 }
 
+/// Storage for string data which may be borrowed from a `&'static str`.
+///
+/// A `Static` either refers to a string with a `'static` lifetime, such as a string literal, or
+/// to an owned [`String`] shared using an [`Arc`]. Creating an
+/// [`ImString`](crate::string::ImString) from a static string using
+/// [`from_static()`](crate::string::ImString::from_static) does not allocate, and neither does
+/// cloning or slicing it. Modifying it copies the data into a newly allocated `String`, after
+/// which it behaves the same as the [`Threadsafe`](crate::string::Threadsafe) storage.
+///
+/// Static data is not reference counted, so its [`ref_count()`](Data::ref_count) is 1, but
+/// [`get_mut()`](Data::get_mut) only succeeds once the data has been copied. An `ImString` only
+/// reads static data through [`get_borrowed()`](Data::get_borrowed). Since
+/// [`get()`](Data::get) has to return a reference to a `String`, calling it directly on static
+/// data copies it into a `String` the first time, which is kept until the storage is dropped.
+pub struct Static(StaticData);
+
+enum StaticData {
+    Borrowed(&'static str, OnceLock<String>),
+    Owned(Arc<String>),
+}
+
+impl Static {
+    /// Creates storage referring to a static string, without allocating.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Static};
+    ///
+    /// let data = Static::from_static("hello");
+    /// assert_eq!(data.get_borrowed(), "hello");
+    /// ```
+    pub const fn from_static(string: &'static str) -> Self {
+        Static(StaticData::Borrowed(string, OnceLock::new()))
+    }
+}
+
+impl Clone for Static {
+    fn clone(&self) -> Self {
+        match &self.0 {
+            StaticData::Borrowed(string, _) => Static::from_static(string),
+            StaticData::Owned(string) => Static(StaticData::Owned(string.clone())),
+        }
+    }
+}
+
+impl Data<String> for Static {
+    fn new(value: String) -> Self {
+        Static(StaticData::Owned(Arc::new(value)))
+    }
+
+    fn get(&self) -> &String {
+        match &self.0 {
+            StaticData::Borrowed(string, owned) => owned.get_or_init(|| string.to_string()),
+            StaticData::Owned(string) => string,
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut String> {
+        match &mut self.0 {
+            StaticData::Borrowed(..) => None,
+            StaticData::Owned(string) => Arc::get_mut(string),
+        }
+    }
+
+    fn ref_count(&self) -> usize {
+        match &self.0 {
+            StaticData::Borrowed(..) => 1,
+            StaticData::Owned(string) => Arc::strong_count(string),
+        }
+    }
+
+    fn get_borrowed(&self) -> &str {
+        match &self.0 {
+            StaticData::Borrowed(string, _) => string,
+            StaticData::Owned(string) => string,
+        }
+    }
+
+    fn get_owned(&self) -> Option<&String> {
+        match &self.0 {
+            StaticData::Borrowed(..) => None,
+            StaticData::Owned(string) => Some(string),
+        }
+    }
+}
+
+/// Storage for string data which keeps small strings inline.
+///
+/// Strings which fit into `N` bytes, including any spare capacity they were created with, are
+/// stored inline without any heap allocation, and are copied when cloned. Other strings are stored
+/// in a `String` shared using an [`Arc`], the same way as the
+/// [`Threadsafe`](crate::string::Threadsafe) storage. This avoids allocations for workloads with
+/// many tiny strings. `N` can be at most 255.
+///
+/// Inline data is not kept in a `String`, so [`get_mut()`](Data::get_mut) and
+/// [`get_owned()`](Data::get_owned) return `None` for it, and its capacity is `N`. Appending to
+/// an inline string or truncating it happens in the inline buffer, while other modifications
+/// store the result inline again as long as it fits. An `ImString` only reads inline data through
+/// [`get_borrowed()`](Data::get_borrowed). Calling [`get()`](Data::get) directly on inline data
+/// copies it into a `String` the first time, which is kept until the data is modified.
+///
+/// Since inline strings are copied when cloned or sliced, they share no memory between clones.
+/// This means that methods which compare the memory of strings, such as
+/// [`byte_offset_in()`](crate::string::ImString::byte_offset_in), treat clones of an inline
+/// string as unrelated strings.
+///
+/// # Example
+///
+/// ```rust
+/// use imstr::data::Inline;
+/// use imstr::string::ImString;
+///
+/// let string: ImString<Inline> = ImString::from("tiny");
+/// assert_eq!(string.slice(1..), "iny");
+/// ```
+///
+/// Inline strings longer than 255 bytes are rejected at compile time:
+///
+/// ```compile_fail
+/// use imstr::data::Inline;
+/// use imstr::string::ImString;
+///
+/// let string: ImString<Inline<256>> = ImString::from("too large");
+/// ```
+pub struct Inline<const N: usize = 22>(InlineData<N>);
+
+enum InlineData<const N: usize> {
+    Inline(u8, [u8; N], OnceLock<String>),
+    Shared(Arc<String>),
+}
+
+impl<const N: usize> Inline<N> {
+    const VALID: () = assert!(N <= 255, "inline strings can be at most 255 bytes");
+
+    fn try_inline(value: &str) -> Option<Self> {
+        let () = Self::VALID;
+        if value.len() > N {
+            return None;
+        }
+        let mut bytes = [0; N];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Some(Inline(InlineData::Inline(
+            value.len() as u8,
+            bytes,
+            OnceLock::new(),
+        )))
+    }
+
+    /// Returns `true` if the data is stored inline.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::{Data, Inline};
+    ///
+    /// let small = <Inline as Data<String>>::new_borrowed("small");
+    /// assert!(small.is_inline());
+    /// let large = <Inline as Data<String>>::new_borrowed("this string is too large to be inline");
+    /// assert!(!large.is_inline());
+    /// ```
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, InlineData::Inline(..))
+    }
+}
+
+impl<const N: usize> Clone for Inline<N> {
+    fn clone(&self) -> Self {
+        match &self.0 {
+            InlineData::Inline(length, bytes, _) => {
+                Inline(InlineData::Inline(*length, *bytes, OnceLock::new()))
+            }
+            InlineData::Shared(string) => Inline(InlineData::Shared(string.clone())),
+        }
+    }
+}
+
+impl<const N: usize> Data<String> for Inline<N> {
+    fn new(value: String) -> Self {
+        if value.capacity() <= N {
+            if let Some(inline) = Inline::try_inline(&value) {
+                return inline;
+            }
+        }
+        Inline(InlineData::Shared(Arc::new(value)))
+    }
+
+    fn new_borrowed(value: &str) -> Self {
+        Inline::try_inline(value)
+            .unwrap_or_else(|| Inline(InlineData::Shared(Arc::new(value.to_string()))))
+    }
+
+    fn get(&self) -> &String {
+        match &self.0 {
+            InlineData::Inline(_, _, owned) => owned.get_or_init(|| self.get_borrowed().into()),
+            InlineData::Shared(string) => string,
+        }
+    }
+
+    fn get_mut(&mut self) -> Option<&mut String> {
+        match &mut self.0 {
+            InlineData::Inline(..) => None,
+            InlineData::Shared(string) => Arc::get_mut(string),
+        }
+    }
+
+    fn ref_count(&self) -> usize {
+        match &self.0 {
+            InlineData::Inline(..) => 1,
+            InlineData::Shared(string) => Arc::strong_count(string),
+        }
+    }
+
+    fn get_borrowed(&self) -> &str {
+        match &self.0 {
+            InlineData::Inline(length, bytes, _) => {
+                // inline data is only ever created from a valid string
+                unsafe { std::str::from_utf8_unchecked(&bytes[..usize::from(*length)]) }
+            }
+            InlineData::Shared(string) => string,
+        }
+    }
+
+    fn get_owned(&self) -> Option<&String> {
+        match &self.0 {
+            InlineData::Inline(..) => None,
+            InlineData::Shared(string) => Some(string),
+        }
+    }
+
+    fn get_mut_borrowed(&mut self) -> Option<&mut str> {
+        match &mut self.0 {
+            InlineData::Inline(length, bytes, owned) => {
+                owned.take();
+                // inline data is only ever created from a valid string
+                Some(unsafe {
+                    std::str::from_utf8_unchecked_mut(&mut bytes[..usize::from(*length)])
+                })
+            }
+            InlineData::Shared(_) => None,
+        }
+    }
+
+    fn inline_capacity(&self) -> usize {
+        match &self.0 {
+            InlineData::Inline(..) => N,
+            InlineData::Shared(_) => 0,
+        }
+    }
+
+    fn try_append(&mut self, len: usize, value: &str) -> bool {
+        match &mut self.0 {
+            InlineData::Inline(length, bytes, owned) if len + value.len() <= N => {
+                owned.take();
+                bytes[len..len + value.len()].copy_from_slice(value.as_bytes());
+                *length = (len + value.len()) as u8;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 #[cfg(test)]
 fn test_i32<T: Data<i32>>() {
     let mut number = T::new(16);
@@ -177,6 +640,10 @@ fn test_i32<T: Data<i32>>() {
     assert_eq!(number.get(), &20);
     let clone = number.clone();
     assert_eq!(clone.get(), number.get());
+    assert!(number.ref_count() >= 1);
+    drop(clone);
+    assert_eq!(number.ref_count(), 1);
+    assert!(number.is_unique());
 }
 
 #[cfg(test)]
@@ -185,8 +652,11 @@ fn test_string<T: Data<String>>() {
     assert_eq!(string.get(), "Hello");
     if let Some(string) = string.get_mut() {
         string.push_str(", World!");
+    } else {
+        assert!(string.try_append(5, ", World!"));
     }
     assert_eq!(string.get(), "Hello, World!");
+    assert_eq!(string.get_borrowed(), "Hello, World!");
     let clone = string.clone();
     assert_eq!(clone.get(), string.get());
 }
@@ -202,4 +672,105 @@ fn test_all_i32() {
     test_string::<Arc<String>>();
     test_string::<Rc<String>>();
     test_string::<Box<String>>();
+    test_string::<Static>();
+    test_string::<Inline>();
+}
+
+#[test]
+fn test_inline() {
+    let mut data = <Inline<4> as Data<String>>::new_borrowed("abcd");
+    assert!(data.is_inline());
+    assert_eq!(data.get_borrowed(), "abcd");
+    assert!(data.clone().is_inline());
+    assert_eq!(data.get_mut(), None);
+    assert_eq!(data.get_owned(), None);
+    assert_eq!(data.ref_count(), 1);
+    let data = <Inline<4> as Data<String>>::new_borrowed("abcde");
+    assert!(!data.is_inline());
+    assert_eq!(data.get_borrowed(), "abcde");
+    let data = <Inline<4> as Data<String>>::new(String::new());
+    assert!(data.is_inline());
+    assert_eq!(data.get_borrowed(), "");
+    let mut data = <Inline<4> as Data<String>>::new("abc".into());
+    assert!(data.is_inline());
+    assert_eq!(data.get_borrowed(), "abc");
+    assert_eq!(data.inline_capacity(), 4);
+    assert_eq!(data.get(), "abc");
+    data.get_mut_borrowed().unwrap().make_ascii_uppercase();
+    assert_eq!(data.get_borrowed(), "ABC");
+    assert_eq!(data.get(), "ABC");
+    assert!(data.try_append(1, "bc"));
+    assert_eq!(data.get_borrowed(), "Abc");
+    assert_eq!(data.get(), "Abc");
+    assert!(data.try_append(3, "d"));
+    assert_eq!(data.get_borrowed(), "Abcd");
+    assert!(!data.try_append(4, "e"));
+    assert_eq!(data.get_borrowed(), "Abcd");
+    let mut data = <Inline<4> as Data<String>>::new(String::with_capacity(16));
+    assert!(!data.is_inline());
+    assert_eq!(data.get_owned().unwrap().capacity(), 16);
+    assert_eq!(data.inline_capacity(), 0);
+    assert_eq!(data.get_mut_borrowed(), None);
+    assert!(!data.try_append(0, "a"));
+    let data = <Inline<255> as Data<String>>::new_borrowed(&"x".repeat(255));
+    assert!(data.is_inline());
+}
+
+#[test]
+fn test_static() {
+    let mut data = Static::from_static("hello");
+    assert_eq!(data.get_borrowed(), "hello");
+    assert_eq!(data.get_mut(), None);
+    assert_eq!(data.get_owned(), None);
+    let clone = data.clone();
+    assert_eq!(clone.get_borrowed().as_ptr(), data.get_borrowed().as_ptr());
+    assert_eq!(data.ref_count(), 1);
+    assert!(data.is_unique());
+    assert_eq!(data.get(), "hello");
+    assert_eq!(data.get_borrowed().as_ptr(), clone.get_borrowed().as_ptr());
+
+    let data = <Static as Data<String>>::new(String::with_capacity(8));
+    assert_eq!(data.get_borrowed(), "");
+    assert_eq!(data.get_owned().unwrap().capacity(), 8);
+}
+
+#[test]
+fn test_default_ref_count() {
+    #[derive(Clone)]
+    struct Custom(String);
+
+    impl Data<String> for Custom {
+        fn new(value: String) -> Self {
+            Custom(value)
+        }
+
+        fn get(&self) -> &String {
+            &self.0
+        }
+
+        fn get_mut(&mut self) -> Option<&mut String> {
+            Some(&mut self.0)
+        }
+    }
+
+    let data = Custom::new("hello".into());
+    assert_eq!(data.ref_count(), 1);
+    assert!(data.is_unique());
+    assert_eq!(data.get(), "hello");
+    assert_eq!(data.get_borrowed(), "hello");
+    assert_eq!(data.get_owned(), Some(&String::from("hello")));
+
+    let string: crate::string::ImString<Custom> = "hello world".into();
+    assert_eq!(string.slice(6..), "world");
+    assert_eq!(string.heap_size(), 11);
+}
+
+#[test]
+fn test_is_unique_weak() {
+    let mut data = Arc::new(String::from("hello"));
+    let weak = Arc::downgrade(&data);
+    assert!(data.is_unique());
+    assert_eq!(data.get_mut(), None);
+    drop(weak);
+    assert!(data.get_mut().is_some());
 }
@@ -10,7 +10,9 @@ pub enum SliceError {
     EndNotAligned,
 }
 
+// this test is kept as originally written, so its lints are allowed
 #[test]
+#[allow(unused_must_use, clippy::clone_on_copy)]
 fn slice_error_debug() {
     let error = SliceError::StartOutOfBounds;
     error.clone();
@@ -1,23 +1,26 @@
-use crate::data::{Cloned, Data};
+#[cfg(test)]
+use crate::data::{Cloned, Inline};
+use crate::data::{Data, Static};
 use crate::error::*;
-use std::borrow::{Borrow, BorrowMut, Cow};
+use std::borrow::{Borrow, Cow};
 use std::cmp::Ordering;
-use std::convert::{AsMut, AsRef, Infallible};
+use std::collections::TryReserveError;
+use std::convert::{AsRef, Infallible};
 use std::ffi::OsStr;
 use std::fmt::{Debug, Display, Error as FmtError, Formatter, Write};
 use std::hash::{Hash, Hasher};
-use std::iter::{Extend, FromIterator};
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::io::{BufRead, Read};
+use std::iter::{Extend, FromIterator, FusedIterator, Sum};
+use std::net::ToSocketAddrs;
 use std::ops::{
-    Add, AddAssign, Bound, Deref, Index, IndexMut, Range, RangeBounds, RangeFrom, RangeFull,
-    RangeInclusive, RangeTo,
+    Add, AddAssign, Bound, Deref, Index, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive,
+    RangeTo,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
 use std::string::{String, ToString};
 use std::sync::Arc;
-use std::vec::IntoIter;
 
 /// Threadsafe shared storage for string.
 pub type Threadsafe = Arc<String>;
@@ -67,8 +70,19 @@ pub type Local = Rc<String>;
 /// let string_slice = string.slice(0..5);
 /// assert_eq!(string_slice, "hello");
 /// ```
+///
+/// The storage type defaults to [`Threadsafe`], but any other [`Data`] implementation can be
+/// chosen explicitly:
+///
+/// ```
+/// use imstr::string::{ImString, Local};
+///
+/// let threadsafe: ImString = ImString::from("hello");
+/// let local: ImString<Local> = ImString::from("hello");
+/// assert_eq!(threadsafe, local);
+/// ```
 #[derive(Clone)]
-pub struct ImString<S: Data<String>> {
+pub struct ImString<S: Data<String> = Threadsafe> {
     /// Underlying string
     string: S,
     /// Offset, must always point to valid UTF-8 region inside string.
@@ -108,11 +122,14 @@ impl<S: Data<String>> ImString<S> {
     /// assert_eq!(string.as_bytes(), &[104, 101, 108, 108, 111]);
     /// ```
     pub fn as_bytes(&self) -> &[u8] {
-        &self.string.get().as_bytes()[self.offset.clone()]
+        &self.string.get_borrowed().as_bytes()[self.offset.clone()]
     }
 
     /// Return the backing [String](std::string::String)'s contents, in bytes.
     ///
+    /// For data which is stored inline, this is the size of the inline buffer, see
+    /// [`Data::inline_capacity`].
+    ///
     /// # Example
     ///
     /// ```rust
@@ -121,7 +138,14 @@ impl<S: Data<String>> ImString<S> {
     /// assert_eq!(string.capacity(), 10);
     /// ```
     pub fn capacity(&self) -> usize {
-        self.string.get().capacity()
+        self.string.get_owned().map_or_else(
+            || {
+                self.string
+                    .inline_capacity()
+                    .max(self.string.get_borrowed().len())
+            },
+            String::capacity,
+        )
     }
 
     /// Create a new `ImString` instance from a standard library [`String`](std::string::String).
@@ -137,7 +161,7 @@ impl<S: Data<String>> ImString<S> {
     /// ```
     pub fn from_std_string(string: String) -> Self {
         ImString {
-            offset: 0..string.as_bytes().len(),
+            offset: 0..string.len(),
             string: S::new(string),
         }
     }
@@ -164,7 +188,7 @@ impl<S: Data<String>> ImString<S> {
     }
 
     unsafe fn try_modify_unchecked<F: FnOnce(&mut String)>(&mut self, f: F) -> bool {
-        if let Some(mut string) = self.string.get_mut() {
+        if let Some(string) = self.string.get_mut() {
             f(string);
             true
         } else {
@@ -174,6 +198,11 @@ impl<S: Data<String>> ImString<S> {
 
     /// Creates a new string with the given capacity.
     ///
+    /// Note that the capacity can only be used for appending in place as long as this string is
+    /// the only reference to its underlying data. Once it is cloned, appending copies the data
+    /// into a new buffer. Use [`ensure_unique_capacity()`](ImString::ensure_unique_capacity) to
+    /// get a uniquely owned buffer with spare capacity again.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -187,7 +216,8 @@ impl<S: Data<String>> ImString<S> {
 
     /// Returns the length of the string in bytes.
     ///
-    /// This will not return the length in `char`s or graphemes.
+    /// This will not return the length in `char`s or graphemes, use
+    /// [`char_len()`](ImString::char_len) for the former.
     ///
     /// # Example
     ///
@@ -200,6 +230,24 @@ impl<S: Data<String>> ImString<S> {
         self.offset.len()
     }
 
+    /// Returns the length of the string in `char`s.
+    ///
+    /// This is the same as `chars().count()`. Note that unlike [`len()`](ImString::len), this is
+    /// an *O(n)* operation, since it needs to decode the entire string. If the length is needed
+    /// repeatedly, consider storing it rather than calling this method every time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("gr\u{fc}\u{df}e");
+    /// assert_eq!(string.len(), 7);
+    /// assert_eq!(string.char_len(), 5);
+    /// ```
+    pub fn char_len(&self) -> usize {
+        self.chars().count()
+    }
+
     /// Convert this string into a standard library [String](std::string::String).
     ///
     /// If this string has no other clones, it will return the `String` without needing to clone
@@ -216,7 +264,7 @@ impl<S: Data<String>> ImString<S> {
             return self.as_str().to_string();
         }
 
-        if let Some(mut string) = self.string.get_mut() {
+        if let Some(string) = self.string.get_mut() {
             if string.len() != self.offset.end {
                 string.truncate(self.offset.end);
             }
@@ -227,6 +275,25 @@ impl<S: Data<String>> ImString<S> {
         }
     }
 
+    /// Converts this string into an owned [`PathBuf`].
+    ///
+    /// This always copies the contents of this string, use the [`From`] implementation to reuse
+    /// the underlying data when this string has no other clones.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// # use std::path::Path;
+    /// let string = ImString::from("/usr/share/doc");
+    /// let path = string.to_path_buf();
+    /// assert_eq!(path, Path::new("/usr/share/doc"));
+    /// assert_eq!(path.file_name().unwrap(), "doc");
+    /// ```
+    pub fn to_path_buf(&self) -> PathBuf {
+        PathBuf::from(self.as_str())
+    }
+
     /// Creates a new, empty `ImString`.
     ///
     /// # Example
@@ -250,7 +317,7 @@ impl<S: Data<String>> ImString<S> {
     /// assert_eq!(string.as_str(), "hello");
     /// ```
     pub fn as_str(&self) -> &str {
-        let slice = &self.string.get().as_bytes()[self.offset.start..self.offset.end];
+        let slice = &self.string.get_borrowed().as_bytes()[self.offset.start..self.offset.end];
         unsafe { std::str::from_utf8_unchecked(slice) }
     }
 
@@ -292,33 +359,100 @@ impl<S: Data<String>> ImString<S> {
         ImString::from_std_string(string)
     }
 
+    /// Decodes a UTF-16 encoded slice into an `ImString`, returning an error if it contains
+    /// invalid data.
+    ///
+    /// See [`String::from_utf16()`] for more details on this function.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let music = [0xD834, 0xDD1E, 0x006d, 0x0075, 0x0073, 0x0069, 0x0063];
+    /// assert_eq!(ImString::from_utf16(&music).unwrap(), "\u{1d11e}music");
+    ///
+    /// let invalid = [0xD834, 0xDD1E, 0x006d, 0x0075, 0xD800, 0x0069, 0x0063];
+    /// assert!(ImString::from_utf16(&invalid).is_err());
+    /// ```
+    pub fn from_utf16(data: &[u16]) -> Result<Self, FromUtf16Error> {
+        Ok(ImString::from_std_string(String::from_utf16(data)?))
+    }
+
+    /// Decodes a UTF-16 encoded slice into an `ImString`, replacing invalid data with the
+    /// replacement character (`U+FFFD`).
+    ///
+    /// See [`String::from_utf16_lossy()`] for more details on this function.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let music = [0xD834, 0xDD1E, 0x006d, 0x0075, 0x0073, 0xDD1E, 0x0069, 0x0063, 0xD834];
+    /// assert_eq!(ImString::from_utf16_lossy(&music), "\u{1d11e}mus\u{FFFD}ic\u{FFFD}");
+    /// ```
+    pub fn from_utf16_lossy(data: &[u16]) -> Self {
+        ImString::from_std_string(String::from_utf16_lossy(data))
+    }
+
     /// Converts a vector of bytes to a ImString.
+    ///
+    /// # Safety
+    ///
+    /// The bytes passed in must be valid UTF-8, see [`String::from_utf8_unchecked()`].
     pub unsafe fn from_utf8_unchecked(vec: Vec<u8>) -> Self {
         ImString::from_std_string(String::from_utf8_unchecked(vec))
     }
 
-    unsafe fn unchecked_append<F: FnOnce(String) -> String>(&mut self, f: F) {
+    /// Modifies the underlying data in place if possible, or a copy of this string otherwise.
+    ///
+    /// The copy is made with room for `additional` more bytes, so that modifications which grow
+    /// this string by at most that much do not reallocate it.
+    unsafe fn unchecked_append<F: FnOnce(String) -> String>(&mut self, additional: usize, f: F) {
         match self.string.get_mut() {
-            Some(mut string_ref) if self.offset.start == 0 => {
-                let mut string: String = std::mem::take(&mut string_ref);
+            Some(string_ref) if self.offset.start == 0 => {
+                let mut string: String = std::mem::take(string_ref);
                 string.truncate(self.offset.end);
                 *string_ref = f(string);
             }
             _ => {
-                self.string = S::new(f(self.as_str().to_string()));
+                let mut string = String::with_capacity(self.len() + additional);
+                string.push_str(self.as_str());
+                self.string = S::new(f(string));
                 self.offset.start = 0;
             }
         }
 
-        self.offset.end = self.string.get().as_bytes().len();
+        self.offset.end = self.string.get_borrowed().len();
+    }
+
+    /// Appends the slice to the underlying data in place, if this string is the only reference to
+    /// it and extends to its end, or if the storage can append to it directly (see
+    /// [`Data::try_append`]). Returns `false` if that is not possible.
+    fn try_push_in_place(&mut self, slice: &str) -> bool {
+        let end = self.offset.end;
+        if let Some(string) = self.string.get_mut() {
+            if string.len() != end {
+                return false;
+            }
+            string.push_str(slice);
+        } else if !self.string.try_append(end, slice) {
+            return false;
+        }
+        self.offset.end = end + slice.len();
+        true
     }
 
     /// Inserts a character into this string at the specified index.
     ///
-    /// This is an *O(n)$ operation as it requires copying every element in the buffer.
+    /// This is an *O(n)* operation as it requires copying every element in the buffer. Inserting
+    /// at the end of a string which is the only reference to its underlying data is done in place
+    /// instead.
     pub fn insert(&mut self, index: usize, c: char) {
+        if index == self.len() && self.try_push_in_place(c.encode_utf8(&mut [0; 4])) {
+            return;
+        }
         unsafe {
-            self.unchecked_append(|mut string| {
+            self.unchecked_append(c.len_utf8(), |mut string| {
                 string.insert(index, c);
                 string
             });
@@ -327,7 +461,9 @@ impl<S: Data<String>> ImString<S> {
 
     /// Inserts a string into this string at the specified index.
     ///
-    /// This is an *O(n)$ operation as it requires copying every element in the buffer.
+    /// This is an *O(n)* operation as it requires copying every element in the buffer. Inserting
+    /// at the end of a string which is the only reference to its underlying data is done in place
+    /// instead.
     ///
     /// # Example
     ///
@@ -338,29 +474,87 @@ impl<S: Data<String>> ImString<S> {
     /// assert_eq!(string, "Hello, World!");
     /// ```
     pub fn insert_str(&mut self, index: usize, s: &str) {
+        if index == self.len() && self.try_push_in_place(s) {
+            return;
+        }
         unsafe {
-            self.unchecked_append(|mut string| {
+            self.unchecked_append(s.len(), |mut string| {
                 string.insert_str(index, s);
                 string
             });
         }
     }
 
+    /// Shortens this string to the specified length in bytes.
+    ///
+    /// If `length` is greater than or equal to the current length, this has no effect. If this
+    /// is the only reference to the underlying data, the removed bytes are dropped from it,
+    /// otherwise only the offset is adjusted.
+    ///
+    /// This will panic if `length` does not lie on a char boundary, the same way as
+    /// [`String::truncate`]. Use the [try_truncate](ImString::try_truncate) method if you want
+    /// to handle this error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// string.truncate(2);
+    /// assert_eq!(string, "he");
+    /// ```
     pub fn truncate(&mut self, length: usize) {
-        // actual new length
-        let length = self.offset.start + length;
+        self.try_truncate(length).unwrap()
+    }
+
+    /// Shortens this string to the specified length in bytes, returning an error if `length`
+    /// does not lie on a char boundary.
+    ///
+    /// This works the same way as [`truncate()`](ImString::truncate), but instead of panicking it
+    /// returns [`SliceError::EndNotAligned`] and leaves this string unchanged if `length` is in
+    /// the middle of a char. If `length` is greater than or equal to the current length, this
+    /// has no effect and returns `Ok(())`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// # use imstr::error::SliceError;
+    /// let mut string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.try_truncate(2), Err(SliceError::EndNotAligned));
+    /// assert_eq!(string, "h\u{e9}llo");
+    /// assert_eq!(string.try_truncate(10), Ok(()));
+    /// assert_eq!(string, "h\u{e9}llo");
+    /// assert_eq!(string.try_truncate(3), Ok(()));
+    /// assert_eq!(string, "h\u{e9}");
+    /// ```
+    pub fn try_truncate(&mut self, length: usize) -> Result<(), SliceError> {
+        if length >= self.len() {
+            return Ok(());
+        }
+
+        if !self.as_str().is_char_boundary(length) {
+            return Err(SliceError::EndNotAligned);
+        }
+
+        // actual new end
+        let end = self.offset.start + length;
 
         // truncate backing string if possible
-        if let Some(mut string) = self.string.get_mut() {
-            string.truncate(length);
+        if let Some(string) = self.string.get_mut() {
+            string.truncate(end);
         }
 
-        self.offset.end = self.offset.end.min(length);
+        self.offset.end = end;
+        Ok(())
     }
 
     pub fn push(&mut self, c: char) {
+        if self.try_push_in_place(c.encode_utf8(&mut [0; 4])) {
+            return;
+        }
         unsafe {
-            self.unchecked_append(|mut string| {
+            self.unchecked_append(c.len_utf8(), |mut string| {
                 string.push(c);
                 string
             });
@@ -368,8 +562,11 @@ impl<S: Data<String>> ImString<S> {
     }
 
     pub fn push_str(&mut self, slice: &str) {
+        if self.try_push_in_place(slice) {
+            return;
+        }
         unsafe {
-            self.unchecked_append(|mut string| {
+            self.unchecked_append(slice.len(), |mut string| {
                 string.push_str(slice);
                 string
             });
@@ -402,17 +599,22 @@ impl<S: Data<String>> ImString<S> {
 
     pub fn try_slice(&self, range: impl RangeBounds<usize>) -> Result<Self, SliceError> {
         let start = match range.start_bound() {
-            Bound::Included(value) => *value,
-            Bound::Excluded(value) => *value + 1,
-            Bound::Unbounded => 0,
+            Bound::Included(value) => Some(*value),
+            Bound::Excluded(value) => value.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let start = match start {
+            Some(start) if start <= self.offset.len() => start,
+            _ => return Err(SliceError::StartOutOfBounds),
         };
-        if start > self.offset.len() {
-            return Err(SliceError::StartOutOfBounds);
-        }
         let end = match range.end_bound() {
-            Bound::Included(value) => *value - 1,
-            Bound::Excluded(value) => *value,
-            Bound::Unbounded => self.offset.len(),
+            Bound::Included(value) => value.checked_add(1),
+            Bound::Excluded(value) => Some(*value),
+            Bound::Unbounded => Some(self.offset.len()),
+        };
+        let end = match end {
+            Some(end) => end,
+            None => return Err(SliceError::EndOutOfBounds),
         };
         if end < start {
             return Err(SliceError::EndBeforeStart);
@@ -426,10 +628,16 @@ impl<S: Data<String>> ImString<S> {
         if !self.as_str().is_char_boundary(end) {
             return Err(SliceError::EndNotAligned);
         }
-        let slice = unsafe { self.slice_unchecked(range) };
+        let slice = unsafe { self.slice_unchecked(start..end) };
         Ok(slice)
     }
 
+    /// Create a subslice of this string without checking the range.
+    ///
+    /// # Safety
+    ///
+    /// The range must be within the bounds of this string and both ends must lie on UTF-8 char
+    /// boundaries, otherwise the resulting `ImString` will point to invalid UTF-8.
     pub unsafe fn slice_unchecked(&self, range: impl RangeBounds<usize>) -> Self {
         let start = match range.start_bound() {
             Bound::Included(value) => *value,
@@ -437,7 +645,7 @@ impl<S: Data<String>> ImString<S> {
             Bound::Unbounded => 0,
         };
         let end = match range.end_bound() {
-            Bound::Included(value) => *value - 1,
+            Bound::Included(value) => *value + 1,
             Bound::Excluded(value) => *value,
             Bound::Unbounded => self.offset.len(),
         };
@@ -457,7 +665,7 @@ impl<S: Data<String>> ImString<S> {
     }
 
     pub fn try_slice_ref(&self, slice: &[u8]) -> Option<Self> {
-        try_slice_offset(self.string.get().as_bytes(), slice).map(|range| ImString {
+        try_slice_offset(self.string.get_borrowed().as_bytes(), slice).map(|range| ImString {
             offset: range,
             ..self.clone()
         })
@@ -467,8 +675,33 @@ impl<S: Data<String>> ImString<S> {
         self.try_slice_ref(slice).unwrap()
     }
 
+    /// Returns the byte range of this string relative to `other`.
+    ///
+    /// If this string is a view into the same underlying data as `other`, and lies completely
+    /// within the part of it that `other` refers to, this returns the range such that indexing
+    /// `other` with it yields this string. Returns `None` if this string does not share data
+    /// with `other`, or if it is not contained within it.
+    ///
+    /// Strings with [`Inline`](crate::data::Inline) or [`Cloned`](crate::data::Cloned) storage
+    /// share no memory between clones, so for them this returns `None` when this string is a
+    /// clone or slice of `other`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let document = ImString::from("let answer = 42;");
+    /// let token = document.slice(4..10);
+    /// assert_eq!(token.byte_offset_in(&document), Some(4..10));
+    /// assert_eq!(&document[token.byte_offset_in(&document).unwrap()], "answer");
+    /// assert_eq!(token.byte_offset_in(&ImString::from("answer")), None);
+    /// ```
+    pub fn byte_offset_in(&self, other: &Self) -> Option<Range<usize>> {
+        try_slice_offset(other.as_bytes(), self.as_bytes())
+    }
+
     pub fn try_split_off(&mut self, position: usize) -> Option<Self> {
-        if position > self.offset.end {
+        if position > self.len() {
             return None;
         }
 
@@ -476,6 +709,7 @@ impl<S: Data<String>> ImString<S> {
             return None;
         }
 
+        let position = self.offset.start + position;
         let new = ImString {
             offset: position..self.offset.end,
             ..self.clone()
@@ -489,6 +723,90 @@ impl<S: Data<String>> ImString<S> {
         self.try_split_off(position).unwrap()
     }
 
+    /// Splits off the last `position` bytes of this string and returns them.
+    ///
+    /// This works like [`split_off()`](ImString::split_off), except that the position is counted
+    /// from the end of this string. Afterwards, this string contains everything but the last
+    /// `position` bytes. The returned `ImString` shares the underlying data with this string.
+    ///
+    /// This will panic if `position` is larger than the length of this string or if the split
+    /// point does not lie on a char boundary. Use the
+    /// [try_split_off_back](ImString::try_split_off_back) method if you want to handle these
+    /// errors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("report.pdf");
+    /// let extension = string.split_off_back(4);
+    /// assert_eq!(string, "report");
+    /// assert_eq!(extension, ".pdf");
+    /// ```
+    pub fn split_off_back(&mut self, position: usize) -> Self {
+        self.try_split_off_back(position).unwrap()
+    }
+
+    pub fn try_split_off_back(&mut self, position: usize) -> Option<Self> {
+        let position = self.len().checked_sub(position)?;
+        self.try_split_off(position)
+    }
+
+    /// Removes the first `count` bytes from this string and returns them.
+    ///
+    /// This will panic if `count` is larger than the length of this string or if it does not lie
+    /// on a character boundary. Use the [try_take](ImString::try_take) method if you want to
+    /// handle these errors. The returned `ImString` shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("key=value");
+    /// assert_eq!(string.take(3), "key");
+    /// assert_eq!(string, "=value");
+    /// ```
+    pub fn take(&mut self, count: usize) -> Self {
+        self.try_take(count).unwrap()
+    }
+
+    pub fn try_take(&mut self, count: usize) -> Result<Self, SliceError> {
+        let front = self.try_slice(..count)?;
+        self.offset.start += count;
+        Ok(front)
+    }
+
+    /// Removes the first `count` bytes from this string.
+    ///
+    /// This will panic if `count` is larger than the length of this string or if it does not lie
+    /// on a character boundary. Use the [try_advance](ImString::try_advance) method if you want
+    /// to handle these errors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("key=value");
+    /// string.advance(4);
+    /// assert_eq!(string, "value");
+    /// ```
+    pub fn advance(&mut self, count: usize) {
+        self.try_advance(count).unwrap()
+    }
+
+    pub fn try_advance(&mut self, count: usize) -> Result<(), SliceError> {
+        if count > self.len() {
+            return Err(SliceError::StartOutOfBounds);
+        }
+
+        if !self.as_str().is_char_boundary(count) {
+            return Err(SliceError::StartNotAligned);
+        }
+
+        self.offset.start += count;
+        Ok(())
+    }
+
     /// Returns a clone of the underlying reference-counted shared `String`.
     ///
     /// This method provides access to the raw `Arc<String>` that backs the `ImString`.
@@ -531,6 +849,95 @@ impl<S: Data<String>> ImString<S> {
         self.offset.clone()
     }
 
+    /// Returns the range this `ImString` covers in the full underlying string.
+    ///
+    /// This is an alias for [`raw_offset()`](ImString::raw_offset), meant to be used together
+    /// with [`full()`](ImString::full): indexing the full string with this range yields this
+    /// string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imstr::ImString;
+    ///
+    /// let string: ImString = ImString::from("hello world");
+    /// let world = string.slice(6..);
+    /// assert_eq!(world.parent_range(), 6..11);
+    /// assert_eq!(&world.full()[world.parent_range()], "world");
+    /// ```
+    pub fn parent_range(&self) -> Range<usize> {
+        self.raw_offset()
+    }
+
+    /// Returns an `ImString` covering the full underlying string.
+    ///
+    /// The returned `ImString` shares the underlying data with this string, but its view spans
+    /// the entire backing string rather than only the part this string refers to. This is
+    /// useful to recover the original input from a slice of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imstr::ImString;
+    ///
+    /// let string: ImString = ImString::from("hello world");
+    /// let hello = string.slice(..5);
+    /// assert_eq!(hello, "hello");
+    /// assert_eq!(hello.full(), "hello world");
+    /// ```
+    pub fn full(&self) -> Self {
+        ImString {
+            offset: 0..self.string.get_borrowed().len(),
+            string: self.string.clone(),
+        }
+    }
+
+    /// Creates an `ImString` from a backing string and an offset into it.
+    ///
+    /// This is the inverse of [`raw_string()`](ImString::raw_string) and
+    /// [`raw_offset()`](ImString::raw_offset). The offset is validated to be within the bounds of
+    /// the backing string and to lie on UTF-8 char boundaries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imstr::ImString;
+    ///
+    /// let string: ImString = ImString::from("hello world");
+    /// let slice = string.slice(6..);
+    /// let parts = ImString::from_parts(slice.raw_string(), slice.raw_offset()).unwrap();
+    /// assert_eq!(parts, "world");
+    /// ```
+    pub fn from_parts(string: S, offset: Range<usize>) -> Result<Self, SliceError> {
+        let backing = string.get_borrowed();
+        if offset.start > backing.len() {
+            return Err(SliceError::StartOutOfBounds);
+        }
+        if offset.end < offset.start {
+            return Err(SliceError::EndBeforeStart);
+        }
+        if offset.end > backing.len() {
+            return Err(SliceError::EndOutOfBounds);
+        }
+        if !backing.is_char_boundary(offset.start) {
+            return Err(SliceError::StartNotAligned);
+        }
+        if !backing.is_char_boundary(offset.end) {
+            return Err(SliceError::EndNotAligned);
+        }
+        Ok(ImString { string, offset })
+    }
+
+    /// Creates an `ImString` from a backing string and an offset into it, without validation.
+    ///
+    /// # Safety
+    ///
+    /// The offset must be within the bounds of the backing string and both ends must lie on UTF-8
+    /// char boundaries, see [`from_parts()`](ImString::from_parts) for a checked version.
+    pub unsafe fn from_parts_unchecked(string: S, offset: Range<usize>) -> Self {
+        ImString { string, offset }
+    }
+
     /// An iterator over the lines of a string.
     ///
     /// Lines are split at line endings that are either newlines (`\n`) or sequences of a carriage
@@ -542,751 +949,7195 @@ impl<S: Data<String>> ImString<S> {
     /// the same lines as an otherwise identical string without a final line ending.
     ///
     /// This works the same way as [String::lines](std::string::String::lines), except that it
-    /// returns ImString instances.
+    /// returns ImString instances. The iterator is double-ended, so lines can also be read from
+    /// the back.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let log = ImString::from("started\nrunning\nstopped\n\n");
+    /// let last = log.lines().rev().find(|line| !line.is_empty()).unwrap();
+    /// assert_eq!(last, "stopped");
+    /// ```
     pub fn lines(&self) -> Lines<'_, S> {
-        ImStringIterator::new(self.string.clone(), self.as_str().lines())
-    }
-}
-
-impl<S: Data<String>> Default for ImString<S> {
-    fn default() -> Self {
-        ImString::new()
+        ImStringIterator::new(&self.string, self.as_str().lines())
     }
-}
 
-impl<S: Data<String>> From<&str> for ImString<S> {
-    fn from(string: &str) -> Self {
-        ImString::from_std_string(string.to_string())
+    /// Returns the number of lines in this string.
+    ///
+    /// Lines are counted the same way [`lines()`](ImString::lines) splits them, so a final line
+    /// ending does not start an additional empty line. This walks the entire string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("first\r\nsecond\nthird");
+    /// assert_eq!(string.line_count(), 3);
+    /// assert_eq!(ImString::from("first\n").line_count(), 1);
+    /// ```
+    pub fn line_count(&self) -> usize {
+        self.as_str().lines().count()
     }
-}
 
-impl<S: Data<String>> From<char> for ImString<S> {
-    fn from(c: char) -> Self {
-        String::from(c).into()
+    /// Returns the line with the given index.
+    ///
+    /// Lines are split the same way as [`lines()`](ImString::lines) does, and do not include the
+    /// line terminator. The returned `ImString` shares the underlying data with this string.
+    /// Returns `None` if there is no line with the given index. This walks the string up to the
+    /// requested line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("first\r\nsecond\nthird");
+    /// assert_eq!(string.line(1).unwrap(), "second");
+    /// assert_eq!(string.line(2).unwrap(), "third");
+    /// assert_eq!(string.line(3), None);
+    /// ```
+    pub fn line(&self, index: usize) -> Option<Self> {
+        self.lines().nth(index)
     }
-}
 
-impl<S: Data<String>> From<String> for ImString<S> {
-    fn from(string: String) -> Self {
+    /// Returns a copy of this string with the first character in uppercase.
+    ///
+    /// If the first character is already uppercase (or has no uppercase form), this returns a
+    /// cheap clone which shares the underlying data. Otherwise, a new string is allocated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello");
+    /// assert_eq!(string.capitalize_first(), "Hello");
+    /// ```
+    pub fn capitalize_first(&self) -> Self {
+        let mut chars = self.chars();
+        let first = match chars.next() {
+            Some(first) => first,
+            None => return self.clone(),
+        };
+        let mut upper = first.to_uppercase();
+        if upper.len() == 1 && upper.next() == Some(first) {
+            return self.clone();
+        }
+        let mut string = String::with_capacity(self.len() + 4);
+        string.extend(first.to_uppercase());
+        string.push_str(chars.as_str());
         ImString::from_std_string(string)
     }
-}
 
-impl<'a, S: Data<String>> From<Cow<'a, str>> for ImString<S> {
-    fn from(string: Cow<'a, str>) -> Self {
-        ImString::from(string.into_owned())
+    /// Clones the contents of this string into an `ImString` with a different storage type.
+    ///
+    /// Since the backing string cannot be shared between different storage types, this copies the
+    /// contents of this string into a new backing string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// use imstr::string::Local;
+    ///
+    /// let string = ImString::from("hello world");
+    /// let local: imstr::string::ImString<Local> = string.slice(6..).clone_as();
+    /// assert_eq!(local, "world");
+    /// ```
+    pub fn clone_as<T: Data<String>>(&self) -> ImString<T> {
+        ImString::from(self.as_str())
     }
-}
 
-impl<S: Data<String>> From<ImString<S>> for String {
-    fn from(string: ImString<S>) -> Self {
-        string.into_std_string()
-    }
-}
+    /// Returns a slice of the first `count` chars of this string.
+    ///
+    /// If this string has fewer than `count` chars, the whole string is returned. This does not
+    /// copy the string data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.prefix(2), "h\u{e9}");
+    /// assert_eq!(string.prefix(10), "h\u{e9}llo");
+    /// ```
+    pub fn prefix(&self, count: usize) -> Self {
+        let end = match self.char_indices().nth(count) {
+            Some((index, _)) => index,
+            None => self.len(),
+        };
+        unsafe { self.slice_unchecked(..end) }
+    }
+
+    /// Returns a slice of the last `count` chars of this string.
+    ///
+    /// If this string has fewer than `count` chars, the whole string is returned. This does not
+    /// copy the string data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hell\u{f6}");
+    /// assert_eq!(string.suffix(2), "l\u{f6}");
+    /// assert_eq!(string.suffix(10), "hell\u{f6}");
+    /// ```
+    pub fn suffix(&self, count: usize) -> Self {
+        let start = match count.checked_sub(1) {
+            Some(count) => match self.char_indices().nth_back(count) {
+                Some((index, _)) => index,
+                None => 0,
+            },
+            None => self.len(),
+        };
+        unsafe { self.slice_unchecked(start..) }
+    }
+
+    /// Translates chars of this string, similar to the Unix `tr` utility.
+    ///
+    /// Every char in this string that occurs in `from` is replaced by the char at the same
+    /// position in `to`, all other chars are left unchanged. If no char of this string occurs in
+    /// `from`, this returns a cheap clone that shares the underlying data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` and `to` do not have the same number of chars.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("cab");
+    /// assert_eq!(string.translate("abc", "xyz"), "zxy");
+    /// ```
+    pub fn translate(&self, from: &str, to: &str) -> Self {
+        let from: Vec<char> = from.chars().collect();
+        let to: Vec<char> = to.chars().collect();
+        assert_eq!(
+            from.len(),
+            to.len(),
+            "translate requires from and to to have the same number of chars"
+        );
+        if !self.chars().any(|c| from.contains(&c)) {
+            return self.clone();
+        }
+        let string = self
+            .chars()
+            .map(|c| match from.iter().position(|f| *f == c) {
+                Some(index) => to[index],
+                None => c,
+            })
+            .collect();
+        ImString::from_std_string(string)
+    }
+
+    /// Returns a slice of this string with leading and trailing whitespace removed.
+    ///
+    /// This works the same way as [`str::trim`], except that it returns an `ImString` which shares
+    /// the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("  hello\t\n");
+    /// assert_eq!(string.trim(), "hello");
+    /// ```
+    pub fn trim(&self) -> Self {
+        self.str_ref(self.as_str().trim())
+    }
+
+    /// Returns a slice of this string with leading whitespace removed.
+    ///
+    /// This works the same way as [`str::trim_start`], except that it returns an `ImString` which
+    /// shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("  hello\t\n");
+    /// assert_eq!(string.trim_start(), "hello\t\n");
+    /// ```
+    pub fn trim_start(&self) -> Self {
+        self.str_ref(self.as_str().trim_start())
+    }
+
+    /// Returns a slice of this string with trailing whitespace removed.
+    ///
+    /// This works the same way as [`str::trim_end`], except that it returns an `ImString` which
+    /// shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("  hello\t\n");
+    /// assert_eq!(string.trim_end(), "  hello");
+    /// ```
+    pub fn trim_end(&self) -> Self {
+        self.str_ref(self.as_str().trim_end())
+    }
+
+    /// Returns a slice of this string with all leading and trailing occurrences of `pattern`
+    /// removed.
+    ///
+    /// This works the same way as [`str::trim_matches`], except that it returns an `ImString`
+    /// which shares the underlying data with this string.
+    ///
+    /// Unlike [`trim_start_matches()`](ImString::trim_start_matches) and
+    /// [`trim_end_matches()`](ImString::trim_end_matches), this takes a `char` rather than a
+    /// `&str`, because [`str::trim_matches`] only accepts patterns which can be searched from both
+    /// ends, and a `&str` pattern cannot. To trim a string pattern from both ends, call both of
+    /// those methods.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("//usr/local//");
+    /// assert_eq!(string.trim_matches('/'), "usr/local");
+    /// let string = ImString::from("<br><br>text<br>");
+    /// assert_eq!(string.trim_start_matches("<br>").trim_end_matches("<br>"), "text");
+    /// ```
+    pub fn trim_matches(&self, pattern: char) -> Self {
+        self.str_ref(self.as_str().trim_matches(pattern))
+    }
+
+    /// Returns a slice of this string with all leading occurrences of `pattern` removed.
+    ///
+    /// This works the same way as [`str::trim_start_matches`], except that it returns an
+    /// `ImString` which shares the underlying data with this string.
+    ///
+    /// Unlike [`trim_matches()`](ImString::trim_matches), which takes a `char`, this takes a
+    /// `&str`, so `pattern` can be longer than one char. A `char` pattern can be passed as a
+    /// string of that char.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("../../src/lib.rs");
+    /// assert_eq!(string.trim_start_matches("../"), "src/lib.rs");
+    /// ```
+    pub fn trim_start_matches(&self, pattern: &str) -> Self {
+        self.str_ref(self.as_str().trim_start_matches(pattern))
+    }
+
+    /// Returns a slice of this string with all trailing occurrences of `pattern` removed.
+    ///
+    /// This works the same way as [`str::trim_end_matches`], except that it returns an
+    /// `ImString` which shares the underlying data with this string.
+    ///
+    /// Unlike [`trim_matches()`](ImString::trim_matches), which takes a `char`, this takes a
+    /// `&str`, so `pattern` can be longer than one char. A `char` pattern can be passed as a
+    /// string of that char.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("value;\r\n\r\n");
+    /// assert_eq!(string.trim_end_matches("\r\n"), "value;");
+    /// ```
+    pub fn trim_end_matches(&self, pattern: &str) -> Self {
+        self.str_ref(self.as_str().trim_end_matches(pattern))
+    }
+
+    /// Returns the number of bytes allocated by the backing string.
+    ///
+    /// This is usually the [`capacity()`](ImString::capacity) of the backing string, which may be
+    /// much larger than the length of this string if it is a slice of a larger string. Storage
+    /// which does not keep its data in a `String`, such as [`Static`] data created from a string
+    /// literal, does not allocate any bytes, see [`Data::get_owned`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::with_capacity(64);
+    /// assert_eq!(string.heap_size(), 64);
+    /// ```
+    pub fn heap_size(&self) -> usize {
+        self.string.get_owned().map_or(0, String::capacity)
+    }
+
+    /// Returns an estimate of the bytes of the backing string attributable to this string.
+    ///
+    /// This divides the [`heap_size()`](ImString::heap_size) by the number of references to the
+    /// backing string, which is useful for accounting of memory kept alive by shared strings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::with_capacity(64);
+    /// let clone = string.clone();
+    /// assert_eq!(string.shared_heap_size(), 32);
+    /// ```
+    pub fn shared_heap_size(&self) -> usize {
+        self.heap_size() / self.string.ref_count()
+    }
+
+    /// Returns a slice of this string with the prefix removed.
+    ///
+    /// This works the same way as [`str::strip_prefix`], except that it returns an `ImString`
+    /// which shares the underlying data with this string. Returns `None` if this string does not
+    /// start with `prefix`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("key=value");
+    /// assert_eq!(string.strip_prefix("key=").unwrap(), "value");
+    /// assert_eq!(string.strip_prefix("value"), None);
+    /// ```
+    pub fn strip_prefix(&self, prefix: &str) -> Option<Self> {
+        self.as_str()
+            .strip_prefix(prefix)
+            .map(|string| self.str_ref(string))
+    }
+
+    /// Returns a slice of this string with the suffix removed.
+    ///
+    /// This works the same way as [`str::strip_suffix`], except that it returns an `ImString`
+    /// which shares the underlying data with this string. Returns `None` if this string does not
+    /// end with `suffix`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("key=value");
+    /// assert_eq!(string.strip_suffix("=value").unwrap(), "key");
+    /// assert_eq!(string.strip_suffix("key"), None);
+    /// ```
+    pub fn strip_suffix(&self, suffix: &str) -> Option<Self> {
+        self.as_str()
+            .strip_suffix(suffix)
+            .map(|string| self.str_ref(string))
+    }
+
+    /// An iterator over the substrings of this string, separated by `pattern`.
+    ///
+    /// This works the same way as [`str::split`], except that it returns ImString instances
+    /// which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a,b,,c");
+    /// let parts: Vec<ImString> = string.split(",").collect();
+    /// assert_eq!(parts, ["a", "b", "", "c"]);
+    /// ```
+    pub fn split<'a>(&'a self, pattern: &'a str) -> Split<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().split(pattern))
+    }
+
+    /// An iterator over the substrings of this string, separated by `pattern`, starting from the
+    /// end of the string.
+    ///
+    /// This works the same way as [`str::rsplit`], except that it returns ImString instances
+    /// which share the underlying data with this string. The first substring is the one after the
+    /// last occurrence of `pattern`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("archive.tar.gz");
+    /// let parts: Vec<ImString> = string.rsplit(".").collect();
+    /// assert_eq!(parts, ["gz", "tar", "archive"]);
+    /// ```
+    pub fn rsplit<'a>(&'a self, pattern: &'a str) -> RSplit<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().rsplit(pattern))
+    }
+
+    /// An iterator over at most `n` substrings of this string, separated by `pattern`.
+    ///
+    /// This works the same way as [`str::splitn`], except that it returns ImString instances
+    /// which share the underlying data with this string. The last substring contains the
+    /// remainder of the string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a,b,c");
+    /// let parts: Vec<ImString> = string.splitn(2, ",").collect();
+    /// assert_eq!(parts, ["a", "b,c"]);
+    /// ```
+    pub fn splitn<'a>(&'a self, n: usize, pattern: &'a str) -> SplitN<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().splitn(n, pattern))
+    }
+
+    /// An iterator over at most `n` substrings of this string, separated by `pattern`, starting
+    /// from the end of the string.
+    ///
+    /// This works the same way as [`str::rsplitn`], except that it returns ImString instances
+    /// which share the underlying data with this string. The last substring contains the
+    /// remainder of the string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a,b,c");
+    /// let parts: Vec<ImString> = string.rsplitn(2, ",").collect();
+    /// assert_eq!(parts, ["c", "a,b"]);
+    /// ```
+    pub fn rsplitn<'a>(&'a self, n: usize, pattern: &'a str) -> RSplitN<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().rsplitn(n, pattern))
+    }
+
+    /// An iterator over the whitespace-separated substrings of this string.
+    ///
+    /// This works the same way as [`str::split_whitespace`], except that it returns ImString
+    /// instances which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from(" hello\t\nworld  ");
+    /// let words: Vec<ImString> = string.split_whitespace().collect();
+    /// assert_eq!(words, ["hello", "world"]);
+    /// ```
+    pub fn split_whitespace(&self) -> SplitWhitespace<'_, S> {
+        ImStringIterator::new(&self.string, self.as_str().split_whitespace())
+    }
+
+    /// An iterator over the ASCII whitespace-separated substrings of this string.
+    ///
+    /// This works the same way as [`str::split_ascii_whitespace`], except that it returns
+    /// ImString instances which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from(" hello\t\nworld  ");
+    /// let words: Vec<ImString> = string.split_ascii_whitespace().collect();
+    /// assert_eq!(words, ["hello", "world"]);
+    /// ```
+    pub fn split_ascii_whitespace(&self) -> SplitAsciiWhitespace<'_, S> {
+        ImStringIterator::new(&self.string, self.as_str().split_ascii_whitespace())
+    }
+
+    /// Divides this string into two slices at the given byte index.
+    ///
+    /// Unlike [`split_off()`](ImString::split_off), this does not modify this string. Both
+    /// returned strings share the underlying data with this string. Returns `None` if `mid` is
+    /// past the end of the string or not on a char boundary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello world");
+    /// let (hello, world) = string.try_split_at(5).unwrap();
+    /// assert_eq!(hello, "hello");
+    /// assert_eq!(world, " world");
+    /// assert_eq!(string.try_split_at(20), None);
+    /// ```
+    pub fn try_split_at(&self, mid: usize) -> Option<(Self, Self)> {
+        if !self.as_str().is_char_boundary(mid) {
+            return None;
+        }
+        let slices = unsafe { (self.slice_unchecked(..mid), self.slice_unchecked(mid..)) };
+        Some(slices)
+    }
+
+    /// Divides this string into two slices at the given byte index.
+    ///
+    /// This will panic if `mid` is past the end of the string or not on a char boundary. Use the
+    /// [try_split_at](ImString::try_split_at) method if you want to handle invalid indices.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello world");
+    /// let (hello, world) = string.split_at(5);
+    /// assert_eq!(hello, "hello");
+    /// assert_eq!(world, " world");
+    /// ```
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        self.try_split_at(mid).unwrap()
+    }
+
+    /// Returns the first char of this string and the rest of it.
+    ///
+    /// The rest is an `ImString` which shares the underlying data with this string. Returns
+    /// `None` if this string is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("\u{e9}t\u{e9}");
+    /// let (first, rest) = string.split_first_char().unwrap();
+    /// assert_eq!(first, '\u{e9}');
+    /// assert_eq!(rest, "t\u{e9}");
+    /// assert_eq!(ImString::new().split_first_char(), None);
+    /// ```
+    pub fn split_first_char(&self) -> Option<(char, Self)> {
+        let c = self.chars().next()?;
+        let rest = unsafe { self.slice_unchecked(c.len_utf8()..) };
+        Some((c, rest))
+    }
+
+    /// Returns the last char of this string and the part of it before that char.
+    ///
+    /// The part before the last char is an `ImString` which shares the underlying data with this
+    /// string. Returns `None` if this string is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("\u{e9}t\u{e9}");
+    /// let (last, rest) = string.split_last_char().unwrap();
+    /// assert_eq!(last, '\u{e9}');
+    /// assert_eq!(rest, "\u{e9}t");
+    /// assert_eq!(ImString::new().split_last_char(), None);
+    /// ```
+    pub fn split_last_char(&self) -> Option<(char, Self)> {
+        let c = self.chars().next_back()?;
+        let rest = unsafe { self.slice_unchecked(..self.len() - c.len_utf8()) };
+        Some((c, rest))
+    }
+
+    /// Replaces all matches of a pattern with another string.
+    ///
+    /// This works the same way as [`str::replace`], except that it returns an `ImString`. If the
+    /// pattern does not occur in this string, this returns a cheap clone which shares the
+    /// underlying data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("this is old");
+    /// assert_eq!(string.replace("old", "new"), "this is new");
+    /// assert_eq!(string.replace("cookie monster", "little lamb"), "this is old");
+    /// ```
+    pub fn replace(&self, from: &str, to: &str) -> Self {
+        if !self.as_str().contains(from) {
+            return self.clone();
+        }
+        ImString::from_std_string(self.as_str().replace(from, to))
+    }
+
+    /// Replaces the first `count` matches of a pattern with another string.
+    ///
+    /// This works the same way as [`str::replacen`], except that it returns an `ImString`. If the
+    /// pattern does not occur in this string or `count` is zero, this returns a cheap clone which
+    /// shares the underlying data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("foo foo 123 foo");
+    /// assert_eq!(string.replacen("foo", "new", 2), "new new 123 foo");
+    /// ```
+    pub fn replacen(&self, from: &str, to: &str, count: usize) -> Self {
+        if count == 0 || !self.as_str().contains(from) {
+            return self.clone();
+        }
+        ImString::from_std_string(self.as_str().replacen(from, to, count))
+    }
+
+    /// Returns the lowercase equivalent of this string.
+    ///
+    /// This works the same way as [`str::to_lowercase`], except that it returns an `ImString`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("HELLO");
+    /// assert_eq!(string.to_lowercase(), "hello");
+    /// ```
+    pub fn to_lowercase(&self) -> Self {
+        ImString::from_std_string(self.as_str().to_lowercase())
+    }
+
+    /// Returns the uppercase equivalent of this string.
+    ///
+    /// This works the same way as [`str::to_uppercase`], except that it returns an `ImString`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello");
+    /// assert_eq!(string.to_uppercase(), "HELLO");
+    /// ```
+    pub fn to_uppercase(&self) -> Self {
+        ImString::from_std_string(self.as_str().to_uppercase())
+    }
+
+    /// Checks if all characters in this string are within the ASCII range.
+    ///
+    /// This works the same way as [`str::is_ascii`]. ASCII strings can be converted with
+    /// [`to_ascii_lowercase`](Self::to_ascii_lowercase) and
+    /// [`to_ascii_uppercase`](Self::to_ascii_uppercase) without copying if they are already in
+    /// the target case.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// assert!(ImString::from("hello, world!").is_ascii());
+    /// assert!(!ImString::from("gr\u{fc}\u{df}e").is_ascii());
+    /// ```
+    pub fn is_ascii(&self) -> bool {
+        self.as_str().is_ascii()
+    }
+
+    /// Returns a copy of this string where each ASCII character is mapped to its lowercase
+    /// equivalent.
+    ///
+    /// This works the same way as [`str::to_ascii_lowercase`], except that it returns an
+    /// `ImString`. If this string contains no uppercase ASCII characters, this returns a cheap
+    /// clone which shares the underlying data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("Gr\u{fc}\u{df}e, J\u{fc}rgen \u{2764}");
+    /// assert_eq!(string.to_ascii_lowercase(), "gr\u{fc}\u{df}e, j\u{fc}rgen \u{2764}");
+    /// ```
+    pub fn to_ascii_lowercase(&self) -> Self {
+        if !self.as_bytes().iter().any(u8::is_ascii_uppercase) {
+            return self.clone();
+        }
+        ImString::from_std_string(self.as_str().to_ascii_lowercase())
+    }
+
+    /// Returns a copy of this string where each ASCII character is mapped to its uppercase
+    /// equivalent.
+    ///
+    /// This works the same way as [`str::to_ascii_uppercase`], except that it returns an
+    /// `ImString`. If this string contains no lowercase ASCII characters, this returns a cheap
+    /// clone which shares the underlying data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("Gr\u{fc}\u{df}e, J\u{fc}rgen \u{2764}");
+    /// assert_eq!(string.to_ascii_uppercase(), "GR\u{fc}\u{df}E, J\u{fc}RGEN \u{2764}");
+    /// ```
+    pub fn to_ascii_uppercase(&self) -> Self {
+        if !self.as_bytes().iter().any(u8::is_ascii_lowercase) {
+            return self.clone();
+        }
+        ImString::from_std_string(self.as_str().to_ascii_uppercase())
+    }
+
+    /// Returns a copy of this string where each ASCII character is mapped to its lowercase
+    /// equivalent.
+    ///
+    /// This is the same as [`to_ascii_lowercase()`](ImString::to_ascii_lowercase): if this string
+    /// contains no uppercase ASCII characters, this returns a cheap clone which shares the
+    /// underlying data, and only allocates otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("content-type");
+    /// assert_eq!(string.ascii_lowercase().as_ptr(), string.as_ptr());
+    /// assert_eq!(ImString::from("Content-Type").ascii_lowercase(), "content-type");
+    /// ```
+    pub fn ascii_lowercase(&self) -> Self {
+        self.to_ascii_lowercase()
+    }
+
+    /// Returns a copy of this string where each ASCII character is mapped to its uppercase
+    /// equivalent.
+    ///
+    /// This is the same as [`to_ascii_uppercase()`](ImString::to_ascii_uppercase): if this string
+    /// contains no lowercase ASCII characters, this returns a cheap clone which shares the
+    /// underlying data, and only allocates otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("GET");
+    /// assert_eq!(string.ascii_uppercase().as_ptr(), string.as_ptr());
+    /// assert_eq!(ImString::from("Get").ascii_uppercase(), "GET");
+    /// ```
+    pub fn ascii_uppercase(&self) -> Self {
+        self.to_ascii_uppercase()
+    }
+
+    /// Checks that this string is an ASCII case-insensitive match for `other`.
+    ///
+    /// This works the same way as [`str::eq_ignore_ascii_case`]. To use strings as keys in a map
+    /// while ignoring their case, wrap them in [`CaseInsensitive`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("Ferris");
+    /// assert!(string.eq_ignore_ascii_case("FERRIS"));
+    /// assert!(!string.eq_ignore_ascii_case("FERRI\u{df}"));
+    /// ```
+    pub fn eq_ignore_ascii_case(&self, other: &str) -> bool {
+        self.as_str().eq_ignore_ascii_case(other)
+    }
+
+    /// Returns a mutable string slice of this string's contents.
+    ///
+    /// If the underlying data is shared with other strings, the contents of this string are
+    /// copied into a new backing string first (copy-on-write), so that mutations are not visible
+    /// through any other string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// let clone = string.clone();
+    /// string.as_mut_str().make_ascii_uppercase();
+    /// assert_eq!(string, "HELLO");
+    /// assert_eq!(clone, "hello");
+    /// ```
+    pub fn as_mut_str(&mut self) -> &mut str {
+        if self.string.get_mut().is_none() && self.string.get_mut_borrowed().is_none() {
+            if self.is_empty() {
+                // empty data might not be kept in a string, but there is nothing to mutate
+                return Default::default();
+            }
+            *self = ImString::from_std_string(self.as_str().to_string());
+        }
+        let offset = self.offset.clone();
+        if self.string.get_mut().is_none() {
+            // data which is not kept in a string, such as inline data, can be mutated directly
+            return match self.string.get_mut_borrowed() {
+                Some(string) => &mut string[offset],
+                None => unreachable!("string must be unique after copy-on-write"),
+            };
+        }
+        match self.string.get_mut() {
+            Some(string) => &mut string[offset],
+            None => unreachable!("string must be unique after copy-on-write"),
+        }
+    }
+
+    /// Converts this string to its ASCII uppercase equivalent in-place.
+    ///
+    /// This works the same way as [`str::make_ascii_uppercase`]. If the underlying data is shared
+    /// with other strings, it is copied first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("Gr\u{fc}\u{df}e");
+    /// string.make_ascii_uppercase();
+    /// assert_eq!(string, "GR\u{fc}\u{df}E");
+    /// ```
+    pub fn make_ascii_uppercase(&mut self) {
+        self.as_mut_str().make_ascii_uppercase();
+    }
+
+    /// Converts this string to its ASCII lowercase equivalent in-place.
+    ///
+    /// This works the same way as [`str::make_ascii_lowercase`]. If the underlying data is shared
+    /// with other strings, it is copied first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("GR\u{dc}SSE");
+    /// string.make_ascii_lowercase();
+    /// assert_eq!(string, "gr\u{dc}sse");
+    /// ```
+    pub fn make_ascii_lowercase(&mut self) {
+        self.as_mut_str().make_ascii_lowercase();
+    }
+
+    /// Removes the last character from this string and returns it.
+    ///
+    /// Returns `None` if this string is empty. If this is the only reference to the backing
+    /// string, the character is removed from it, otherwise only the offset is adjusted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("ab\u{1f600}");
+    /// assert_eq!(string.pop(), Some('\u{1f600}'));
+    /// assert_eq!(string.pop(), Some('b'));
+    /// assert_eq!(string.pop(), Some('a'));
+    /// assert_eq!(string.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.chars().next_back()?;
+        let end = self.offset.end;
+        unsafe {
+            self.try_modify_unchecked(|string| {
+                if string.len() == end {
+                    string.pop();
+                }
+            });
+        }
+        self.offset.end -= c.len_utf8();
+        Some(c)
+    }
+
+    /// Removes a char from this string at a byte position and returns it.
+    ///
+    /// This is an *O(n)* operation, as it requires copying every element after the removed char.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is larger than or equal to the string's length, or if it does not lie on
+    /// a char boundary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("f\u{f6}o");
+    /// assert_eq!(string.remove(1), '\u{f6}');
+    /// assert_eq!(string, "fo");
+    /// ```
+    pub fn remove(&mut self, index: usize) -> char {
+        let c = match self.as_str()[index..].chars().next() {
+            Some(c) => c,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.remove(index);
+                string
+            });
+        }
+        c
+    }
+
+    /// Resolves a range of byte indices into this string, panicking like slice indexing does.
+    fn resolve_range(&self, range: impl RangeBounds<usize>) -> Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(value) => *value,
+            Bound::Excluded(value) => value
+                .checked_add(1)
+                .expect("attempted to index string from after maximum usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(value) => value
+                .checked_add(1)
+                .expect("attempted to index string up to maximum usize"),
+            Bound::Excluded(value) => *value,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(start <= end, "range start {start} is after range end {end}");
+        assert!(end <= self.len(), "range end {end} is out of bounds");
+        assert!(
+            self.as_str().is_char_boundary(start),
+            "range start is not on a char boundary"
+        );
+        assert!(
+            self.as_str().is_char_boundary(end),
+            "range end is not on a char boundary"
+        );
+        start..end
+    }
+
+    /// Removes the specified byte range from this string, returning the removed chars as an
+    /// iterator.
+    ///
+    /// The range is removed from this string immediately, even if the iterator is not consumed.
+    /// If the underlying data is shared, the removed chars are a view into it, otherwise they are
+    /// copied out before the range is removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point or end point do not lie on a char boundary, or if they are
+    /// out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("\u{3b1} is alpha, \u{3b2} is beta");
+    /// let beta_offset = string.find('\u{3b2}').unwrap();
+    /// let removed: String = string.drain(..beta_offset).collect();
+    /// assert_eq!(removed, "\u{3b1} is alpha, ");
+    /// assert_eq!(string, "\u{3b2} is beta");
+    /// ```
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<S> {
+        let range = self.resolve_range(range);
+        let removed = match self.string.get_mut() {
+            Some(_) => ImString::from(&self.as_str()[range.clone()]),
+            None => unsafe { self.slice_unchecked(range.clone()) },
+        };
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.replace_range(range, "");
+                string
+            });
+        }
+        IntoChars { string: removed }
+    }
+
+    /// Removes the specified byte range from this string and replaces it with the given string.
+    ///
+    /// This works the same way as [`String::replace_range`]. The given string does not need to
+    /// be the same length as the range. If the underlying data is shared, the contents are copied
+    /// into a new buffer first (copy-on-write).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point or end point do not lie on a char boundary, or if they are
+    /// out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("\u{3b1} is alpha, \u{3b2} is beta");
+    /// let beta_offset = string.find('\u{3b2}').unwrap();
+    /// string.replace_range(..beta_offset, "A is capital alpha; ");
+    /// assert_eq!(string, "A is capital alpha; \u{3b2} is beta");
+    /// ```
+    pub fn replace_range(&mut self, range: impl RangeBounds<usize>, replace_with: &str) {
+        let range = self.resolve_range(range);
+        unsafe {
+            self.unchecked_append(replace_with.len(), |mut string| {
+                string.replace_range(range, replace_with);
+                string
+            });
+        }
+    }
+
+    /// Copies the specified byte range of this string and appends it to the end.
+    ///
+    /// This works the same way as the unstable `String::extend_from_within`. If the underlying
+    /// data is shared, the contents are copied into a new buffer first (copy-on-write).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point or end point do not lie on a char boundary, or if they are
+    /// out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("abcde");
+    /// string.extend_from_within(2..);
+    /// assert_eq!(string, "abcdecde");
+    /// string.extend_from_within(..2);
+    /// assert_eq!(string, "abcdecdeab");
+    /// ```
+    pub fn extend_from_within(&mut self, range: impl RangeBounds<usize>) {
+        let range = self.resolve_range(range);
+        unsafe {
+            self.unchecked_append(range.len(), |mut string| {
+                string.as_mut_vec().extend_from_within(range);
+                string
+            });
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more bytes to be appended to this string.
+    ///
+    /// If the underlying data is shared, or this string does not start at the beginning of it,
+    /// the contents are first copied into a new buffer which is then resized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// string.reserve(10);
+    /// assert!(string.capacity() >= 15);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.reserve(additional);
+                string
+            });
+        }
+    }
+
+    /// Ensures that this string has a uniquely owned buffer with room for at least `additional`
+    /// more bytes.
+    ///
+    /// If this string already is the only reference to its underlying data and has enough spare
+    /// capacity after its end, this does nothing. Otherwise, if the underlying data is shared or
+    /// this string does not start at the beginning of it, the contents are copied into a new
+    /// buffer with room for exactly `additional` more bytes. Unlike [`ImString::reserve`], this
+    /// never grows a buffer which already has the requested room. Afterwards, appending up to
+    /// `additional` bytes happens in place without reallocating, as long as this string is not
+    /// cloned in the meantime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// let clone = string.clone();
+    /// string.ensure_unique_capacity(6);
+    /// assert_eq!(string.capacity(), 11);
+    /// let pointer = string.as_ptr();
+    /// string.push_str(" world");
+    /// assert_eq!(string.as_ptr(), pointer);
+    /// assert_eq!(clone, "hello");
+    /// ```
+    pub fn ensure_unique_capacity(&mut self, additional: usize) {
+        let end = self.offset.end;
+        if self.offset.start == 0 {
+            if let Some(string) = self.string.get_mut() {
+                string.truncate(end);
+                string.reserve(additional);
+                return;
+            }
+        }
+        let inline = self.string.get_mut_borrowed().is_some();
+        if inline && self.capacity() - end >= additional {
+            return;
+        }
+        let length = self
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        let mut string = String::with_capacity(length);
+        string.push_str(self.as_str());
+        self.string = S::new(string);
+        self.offset = 0..self.offset.len();
+    }
+
+    /// Reserves the minimum capacity for exactly `additional` more bytes to be appended to this
+    /// string.
+    ///
+    /// This works the same way as [`ImString::reserve`], except that it does not deliberately
+    /// over-allocate. See [`String::reserve_exact`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// string.reserve_exact(10);
+    /// assert!(string.capacity() >= 15);
+    /// ```
+    pub fn reserve_exact(&mut self, additional: usize) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.reserve_exact(additional);
+                string
+            });
+        }
+    }
+
+    /// Tries to reserve capacity for at least `additional` more bytes to be appended to this
+    /// string.
+    ///
+    /// This works the same way as [`ImString::reserve`], except that it returns an error instead
+    /// of panicking or aborting if the capacity overflows or the allocator reports a failure. On
+    /// error, this string is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// assert!(string.try_reserve(10).is_ok());
+    /// assert!(string.capacity() >= 15);
+    /// assert!(string.try_reserve(usize::MAX).is_err());
+    /// assert_eq!(string, "hello");
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        match self.string.get_mut() {
+            Some(string) if self.offset.start == 0 => {
+                string.truncate(self.offset.end);
+                string.try_reserve(additional)
+            }
+            _ => {
+                let mut string = String::new();
+                string.try_reserve_exact(self.len())?;
+                string.push_str(self.as_str());
+                string.try_reserve(additional)?;
+                self.string = S::new(string);
+                self.offset = 0..self.offset.len();
+                Ok(())
+            }
+        }
+    }
+
+    /// Shrinks the capacity of the underlying data to fit the bytes this string refers to.
+    ///
+    /// If this is the only reference to the underlying data, any bytes outside of this string
+    /// are removed and the backing [`String`] is reallocated to fit. Otherwise, only the bytes of
+    /// this string are copied into a new, minimally sized buffer. Data which is not kept on the
+    /// heap, such as [`Static`] or [`Inline`](crate::data::Inline) data, is never copied into a
+    /// new buffer, since that would only increase memory usage.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello, world!").slice(7..12);
+    /// string.shrink_to_fit();
+    /// assert_eq!(string, "world");
+    /// assert!(string.capacity() < "hello, world!".len());
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the capacity of the underlying data with a lower bound.
+    ///
+    /// This works the same way as [`ImString::shrink_to_fit`], except that the capacity will
+    /// remain at least as large as both the length of this string and `min_capacity`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::with_capacity(100);
+    /// string.push_str("hello");
+    /// string.shrink_to(10);
+    /// assert!(string.capacity() >= 10);
+    /// assert!(string.capacity() < 100);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        // data which is not kept in a string does not use any heap memory to free
+        if self.string.get_owned().is_none() {
+            self.shrink_offset_to_fit();
+            return;
+        }
+        match self.string.get_mut() {
+            Some(string) => {
+                string.truncate(self.offset.end);
+                string.replace_range(..self.offset.start, "");
+                string.shrink_to(min_capacity);
+            }
+            None => {
+                let mut string = String::with_capacity(self.len().max(min_capacity));
+                string.push_str(self.as_str());
+                self.string = S::new(string);
+            }
+        }
+        self.offset = 0..self.offset.len();
+    }
+
+    /// Removes the bytes outside of this string from the underlying data, without reallocating.
+    ///
+    /// If this is the only reference to the underlying data, any bytes before and after this
+    /// string are removed from the backing [`String`], so that it contains exactly the bytes of
+    /// this string. Unlike [`ImString::shrink_to_fit`], the capacity is kept, so that it can be
+    /// reused by future appends. If the underlying data is shared, this does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello, world!").slice(7..12);
+    /// let capacity = string.capacity();
+    /// string.shrink_offset_to_fit();
+    /// assert_eq!(string.raw_offset(), 0..5);
+    /// assert_eq!(string.capacity(), capacity);
+    /// ```
+    pub fn shrink_offset_to_fit(&mut self) {
+        if let Some(string) = self.string.get_mut() {
+            string.truncate(self.offset.end);
+            string.replace_range(..self.offset.start, "");
+        } else if self.string.get_mut_borrowed().is_some() {
+            // data which is not kept in a string, such as inline data, is not shared either
+            self.string = S::new_borrowed(self.as_str());
+        } else {
+            return;
+        }
+        self.offset = 0..self.offset.len();
+    }
+
+    /// Returns a deep copy of this string which does not share any data with it.
+    ///
+    /// The returned string is backed by a newly allocated [`String`] containing only the bytes
+    /// of this string. This allows keeping a small part of a large string without keeping all of
+    /// the underlying data alive, even if other references to it exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let document = ImString::from("title: hello\nbody: world");
+    /// let title = document.slice(7..12).compact();
+    /// drop(document);
+    /// assert_eq!(title, "hello");
+    /// assert_eq!(title.capacity(), title.len());
+    /// ```
+    pub fn compact(&self) -> Self {
+        ImString::from(self.as_str())
+    }
+
+    /// Returns the number of references to the underlying data.
+    ///
+    /// Every clone and slice of a string shares its underlying data. For storage types which do
+    /// not share data, such as [`Cloned`](crate::data::Cloned) or [`Box`], this is always 1.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello");
+    /// assert_eq!(string.ref_count(), 1);
+    /// let clone = string.clone();
+    /// assert_eq!(string.ref_count(), 2);
+    /// drop(clone);
+    /// assert_eq!(string.ref_count(), 1);
+    /// ```
+    pub fn ref_count(&self) -> usize {
+        self.string.ref_count()
+    }
+
+    /// Returns `true` if no other string shares the underlying data.
+    ///
+    /// If this returns `false`, mutating this string will first copy its contents into a new
+    /// buffer. For storage types which do not share data, such as
+    /// [`Cloned`](crate::data::Cloned) or [`Box`], this is always `true`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello world");
+    /// assert!(string.is_unique());
+    /// let hello = string.slice(..5);
+    /// assert!(!string.is_unique());
+    /// ```
+    pub fn is_unique(&self) -> bool {
+        self.string.is_unique()
+    }
+
+    /// Converts this string into a byte vector.
+    ///
+    /// If this string has no other clones and starts at the beginning of the underlying data,
+    /// this reuses its allocation. Otherwise, the bytes are copied.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello");
+    /// assert_eq!(string.into_bytes(), b"hello");
+    /// ```
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.into_std_string().into_bytes()
+    }
+
+    /// Splits this string on the first occurrence of the delimiter.
+    ///
+    /// This works the same way as [`str::split_once`], except that it returns `ImString`s which
+    /// share the underlying data with this string. Returns `None` if the delimiter does not occur
+    /// in this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("key=value=other");
+    /// let (key, value) = string.split_once("=").unwrap();
+    /// assert_eq!(key, "key");
+    /// assert_eq!(value, "value=other");
+    /// assert_eq!(string.split_once(":"), None);
+    /// ```
+    pub fn split_once(&self, delimiter: &str) -> Option<(Self, Self)> {
+        self.as_str()
+            .split_once(delimiter)
+            .map(|(left, right)| (self.str_ref(left), self.str_ref(right)))
+    }
+
+    /// Splits this string on the last occurrence of the delimiter.
+    ///
+    /// This works the same way as [`str::rsplit_once`], except that it returns `ImString`s which
+    /// share the underlying data with this string. Returns `None` if the delimiter does not occur
+    /// in this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("key=value=other");
+    /// let (key, value) = string.rsplit_once("=").unwrap();
+    /// assert_eq!(key, "key=value");
+    /// assert_eq!(value, "other");
+    /// assert_eq!(string.rsplit_once(":"), None);
+    /// ```
+    pub fn rsplit_once(&self, delimiter: &str) -> Option<(Self, Self)> {
+        self.as_str()
+            .rsplit_once(delimiter)
+            .map(|(left, right)| (self.str_ref(left), self.str_ref(right)))
+    }
+
+    /// Returns an iterator over the chars of this string and their byte positions, where each
+    /// char is an `ImString` which shares the underlying data with this string.
+    ///
+    /// This works the same way as [`str::char_indices`], except that it yields single-char
+    /// `ImString` slices instead of `char` values.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a\u{e4}b");
+    /// let mut chars = string.char_slices();
+    /// assert_eq!(chars.next(), Some((0, ImString::from("a"))));
+    /// assert_eq!(chars.next(), Some((1, ImString::from("\u{e4}"))));
+    /// assert_eq!(chars.next(), Some((3, ImString::from("b"))));
+    /// assert_eq!(chars.next(), None);
+    /// ```
+    pub fn char_slices(&self) -> CharSlices<'_, S> {
+        CharSlices {
+            string: &self.string,
+            slice: self.as_str(),
+            iterator: self.as_str().char_indices(),
+        }
+    }
+
+    /// Returns an iterator over chunks of at most `size` bytes of this string, where each chunk
+    /// is an `ImString` which shares the underlying data with this string.
+    ///
+    /// Chunks never split a char: the end of every chunk is moved back to the previous char
+    /// boundary if needed. A char which is wider than `size` bytes is emitted as a chunk of its
+    /// own, so every chunk contains at least one char.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("ab\u{e9}cd\u{1f600}");
+    /// let chunks: Vec<ImString> = string.chunks_bytes(3).collect();
+    /// assert_eq!(chunks, ["ab", "\u{e9}c", "d", "\u{1f600}"]);
+    /// ```
+    pub fn chunks_bytes(&self, size: usize) -> ChunksBytes<S> {
+        assert!(size != 0, "chunk size must be non-zero");
+        let mut chunks = ChunksBytes {
+            string: self.clone(),
+            size,
+            remaining: 0,
+        };
+        let mut string = self.as_str();
+        while !string.is_empty() {
+            string = &string[chunks.chunk_len(string)..];
+            chunks.remaining += 1;
+        }
+        chunks
+    }
+
+    /// Returns an iterator over chunks of at most `size` chars of this string, where each chunk
+    /// is an `ImString` which shares the underlying data with this string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("ab\u{e9}cd\u{1f600}");
+    /// let chunks: Vec<ImString> = string.chunks_chars(4).collect();
+    /// assert_eq!(chunks, ["ab\u{e9}c", "d\u{1f600}"]);
+    /// ```
+    pub fn chunks_chars(&self, size: usize) -> ChunksChars<S> {
+        assert!(size != 0, "chunk size must be non-zero");
+        ChunksChars {
+            string: self.clone(),
+            size,
+            remaining: self.chars().count(),
+        }
+    }
+
+    /// Creates a new string by repeating this string `n` times.
+    ///
+    /// This works the same way as [`str::repeat`]. If `n` is 1, this returns a cheap clone which
+    /// shares the underlying data with this string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity of the resulting string would overflow.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("ab");
+    /// assert_eq!(string.repeat(3), "ababab");
+    /// assert_eq!(string.repeat(0), "");
+    /// ```
+    pub fn repeat(&self, n: usize) -> Self {
+        match n {
+            0 => ImString::new(),
+            1 => self.clone(),
+            n => ImString::from_std_string(self.as_str().repeat(n)),
+        }
+    }
+
+    /// Creates a new string with the characters of this string in reverse order.
+    ///
+    /// This reverses the string per `char` rather than per byte, so the result is always valid
+    /// UTF-8. Note that this means combining characters end up in front of the character they
+    /// were previously attached to. This always allocates a new backing string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.reverse(), "oll\u{e9}h");
+    /// ```
+    pub fn reverse(&self) -> Self {
+        ImString::from_std_string(self.chars().rev().collect())
+    }
+
+    /// Creates a new string with the contents of this string rotated left by `mid` bytes.
+    ///
+    /// The result is the same as `self[mid..] + self[..mid]`. Returns `None` if `mid` is past
+    /// the end of this string or not on a char boundary. Rotating by zero or by the length of
+    /// this string returns a cheap clone which shares the underlying data, otherwise this
+    /// allocates a new backing string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.try_rotate(3).unwrap(), "lloh\u{e9}");
+    /// assert_eq!(string.try_rotate(2), None);
+    /// assert_eq!(string.try_rotate(7), None);
+    /// ```
+    pub fn try_rotate(&self, mid: usize) -> Option<Self> {
+        if !self.as_str().is_char_boundary(mid) {
+            return None;
+        }
+        if mid == 0 || mid == self.len() {
+            return Some(self.clone());
+        }
+        let (left, right) = self.as_str().split_at(mid);
+        let mut string = String::with_capacity(self.len());
+        string.push_str(right);
+        string.push_str(left);
+        Some(ImString::from_std_string(string))
+    }
+
+    /// Creates a new string with the contents of this string rotated left by `mid` bytes.
+    ///
+    /// This will panic if `mid` is past the end of the string or not on a char boundary. Use the
+    /// [try_rotate](ImString::try_rotate) method if you want to handle invalid indices.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("abcdef");
+    /// assert_eq!(string.rotate(2), "cdefab");
+    /// assert_eq!(string.rotate(6), "abcdef");
+    /// ```
+    pub fn rotate(&self, mid: usize) -> Self {
+        self.try_rotate(mid).unwrap()
+    }
+
+    fn pad(&self, width: usize, fill: char, before: impl FnOnce(usize) -> usize) -> Self {
+        let length = self.chars().count();
+        if length >= width {
+            return self.clone();
+        }
+        let padding = width - length;
+        let before = before(padding);
+        let mut string = String::with_capacity(self.len() + padding * fill.len_utf8());
+        string.extend(std::iter::repeat(fill).take(before));
+        string.push_str(self.as_str());
+        string.extend(std::iter::repeat(fill).take(padding - before));
+        ImString::from_std_string(string)
+    }
+
+    /// Pads this string at the start with `fill` until it is at least `width` characters long.
+    ///
+    /// The width is measured in `char`s rather than bytes. If this string is already wide
+    /// enough, this returns a cheap clone which shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("42");
+    /// assert_eq!(string.pad_start(5, '0'), "00042");
+    /// assert_eq!(string.pad_start(1, '0'), "42");
+    /// ```
+    pub fn pad_start(&self, width: usize, fill: char) -> Self {
+        self.pad(width, fill, |padding| padding)
+    }
+
+    /// Pads this string at the end with `fill` until it is at least `width` characters long.
+    ///
+    /// The width is measured in `char`s rather than bytes. If this string is already wide
+    /// enough, this returns a cheap clone which shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("name");
+    /// assert_eq!(string.pad_end(7, '.'), "name...");
+    /// ```
+    pub fn pad_end(&self, width: usize, fill: char) -> Self {
+        self.pad(width, fill, |_| 0)
+    }
+
+    /// Pads this string on both sides with `fill` until it is at least `width` characters long.
+    ///
+    /// The width is measured in `char`s rather than bytes. If the padding cannot be split evenly,
+    /// the extra character is put at the end, the same way the `{:^}` format specifier does. If
+    /// this string is already wide enough, this returns a cheap clone which shares the
+    /// underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("title");
+    /// assert_eq!(string.center(9, '*'), "**title**");
+    /// assert_eq!(string.center(8, '*'), "*title**");
+    /// ```
+    pub fn center(&self, width: usize, fill: char) -> Self {
+        self.pad(width, fill, |padding| padding / 2)
+    }
+
+    /// Concatenates all `parts` into a single string.
+    ///
+    /// The backing string is allocated once with the exact length of the result, which makes
+    /// this more efficient than repeatedly appending the parts. If there is only one part, this
+    /// returns a cheap clone of it which shares its underlying data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let parts = [ImString::from("hello"), ImString::from(" "), ImString::from("world")];
+    /// assert_eq!(ImString::concat(&parts), "hello world");
+    /// ```
+    pub fn concat(parts: &[Self]) -> Self {
+        Self::join("", parts)
+    }
+
+    /// Joins all `parts` into a single string, placing `separator` between each of them.
+    ///
+    /// The backing string is allocated once with the exact length of the result, which makes
+    /// this more efficient than repeatedly appending the parts. If there is only one part, this
+    /// returns a cheap clone of it which shares its underlying data.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let parts = [ImString::from("a"), ImString::from("b"), ImString::from("c")];
+    /// assert_eq!(ImString::join(", ", &parts), "a, b, c");
+    /// ```
+    pub fn join(separator: &str, parts: &[Self]) -> Self {
+        match parts {
+            [] => ImString::new(),
+            [part] => part.clone(),
+            [first, rest @ ..] => {
+                let length = parts.iter().map(|part| part.len()).sum::<usize>()
+                    + separator.len() * rest.len();
+                let mut string = String::with_capacity(length);
+                string.push_str(first);
+                for part in rest {
+                    string.push_str(separator);
+                    string.push_str(part);
+                }
+                ImString::from_std_string(string)
+            }
+        }
+    }
+
+    /// An iterator over the non-overlapping matches of `pattern` in this string.
+    ///
+    /// This works the same way as [`str::matches`], except that it returns ImString instances
+    /// which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("abcXXXabcYYYabc");
+    /// let matches: Vec<ImString> = string.matches("abc").collect();
+    /// assert_eq!(matches, ["abc", "abc", "abc"]);
+    /// ```
+    pub fn matches<'a>(&'a self, pattern: &'a str) -> Matches<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().matches(pattern))
+    }
+
+    /// An iterator over the non-overlapping matches of `pattern` in this string, along with the
+    /// byte index at which they start.
+    ///
+    /// This works the same way as [`str::match_indices`], except that it returns ImString
+    /// instances which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("abcXXXabcYYYabc");
+    /// let matches: Vec<(usize, ImString)> = string.match_indices("abc").collect();
+    /// assert_eq!(matches[1], (6, ImString::from("abc")));
+    /// assert_eq!(matches.len(), 3);
+    /// ```
+    pub fn match_indices<'a>(&'a self, pattern: &'a str) -> MatchIndices<'a, S> {
+        MatchIndices {
+            string: &self.string,
+            iterator: self.as_str().match_indices(pattern),
+        }
+    }
+
+    /// Returns the number of non-overlapping matches of `pattern` in this string.
+    ///
+    /// This is equivalent to `self.matches(pattern).count()`, but does not create an `ImString`
+    /// for every match. An empty pattern matches at every char boundary, like it does for
+    /// [`str::matches`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("abcXXXabcYYYabc");
+    /// assert_eq!(string.count_matches("abc"), 3);
+    /// assert_eq!(string.count_matches("XX"), 1);
+    /// assert_eq!(string.count_matches("abcd"), 0);
+    /// ```
+    pub fn count_matches(&self, pattern: &str) -> usize {
+        self.as_str().matches(pattern).count()
+    }
+
+    /// Returns the first match of `pattern` in this string.
+    ///
+    /// This works like [`str::find`], except that it returns the matched substring as an
+    /// `ImString` which shares the underlying data with this string instead of its byte index.
+    /// Returns `None` if the pattern does not occur in this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("one two one");
+    /// let found = string.find_str("one").unwrap();
+    /// assert_eq!(found, "one");
+    /// assert_eq!(found.as_ptr(), string.as_ptr());
+    /// assert_eq!(string.find_str("three"), None);
+    /// ```
+    pub fn find_str(&self, pattern: &str) -> Option<Self> {
+        self.as_str()
+            .find(pattern)
+            .map(|index| self.slice_ref(&self.as_bytes()[index..index + pattern.len()]))
+    }
+
+    /// Returns the last match of `pattern` in this string.
+    ///
+    /// This works like [`str::rfind`], except that it returns the matched substring as an
+    /// `ImString` which shares the underlying data with this string instead of its byte index.
+    /// Returns `None` if the pattern does not occur in this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("one two one");
+    /// let found = string.rfind_str("one").unwrap();
+    /// assert_eq!(found, "one");
+    /// assert_eq!(found.as_ptr(), string[8..].as_ptr());
+    /// assert_eq!(string.rfind_str("three"), None);
+    /// ```
+    pub fn rfind_str(&self, pattern: &str) -> Option<Self> {
+        self.as_str()
+            .rfind(pattern)
+            .map(|index| self.slice_ref(&self.as_bytes()[index..index + pattern.len()]))
+    }
+
+    /// An iterator over the grapheme clusters of this string.
+    ///
+    /// If `extended` is true, the extended grapheme clusters are used, otherwise the legacy ones.
+    /// See [`UnicodeSegmentation::graphemes`](unicode_segmentation::UnicodeSegmentation::graphemes)
+    /// for details. Each grapheme cluster is returned as an ImString which shares the underlying
+    /// data with this string.
+    ///
+    /// This method is only available with the `unicode` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+    /// let graphemes: Vec<ImString> = string.graphemes(true).collect();
+    /// assert_eq!(graphemes, ["a\u{310}", "e\u{301}", "o\u{308}\u{332}"]);
+    /// ```
+    #[cfg(feature = "unicode")]
+    pub fn graphemes(&self, extended: bool) -> Graphemes<'_, S> {
+        use unicode_segmentation::UnicodeSegmentation;
+        ImStringIterator::new(&self.string, self.as_str().graphemes(extended))
+    }
+
+    /// Creates a subslice of this string, using `boundaries` to translate the range into byte
+    /// indices. `boundaries(n)` returns the byte index of the `n`-th boundary, if it exists.
+    fn try_slice_by<F: FnMut(usize) -> Option<usize>>(
+        &self,
+        range: impl RangeBounds<usize>,
+        mut boundaries: F,
+    ) -> Result<Self, SliceError> {
+        let start = match range.start_bound() {
+            Bound::Included(value) => Some(*value),
+            Bound::Excluded(value) => value.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let start = start
+            .and_then(&mut boundaries)
+            .ok_or(SliceError::StartOutOfBounds)?;
+        let end = match range.end_bound() {
+            Bound::Included(value) => value.checked_add(1).and_then(&mut boundaries),
+            Bound::Excluded(value) => boundaries(*value),
+            Bound::Unbounded => Some(self.len()),
+        };
+        let end = end.ok_or(SliceError::EndOutOfBounds)?;
+        if end < start {
+            return Err(SliceError::EndBeforeStart);
+        }
+        Ok(unsafe { self.slice_unchecked(start..end) })
+    }
+
+    /// Create a subslice of this string, using char indices instead of byte indices.
+    ///
+    /// This will panic if the specified range is invalid. Use the
+    /// [try_slice_chars](ImString::try_slice_chars) method if you want to handle invalid ranges.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("\u{1f600}\u{1f603}\u{1f604}\u{1f601}");
+    /// assert_eq!(string.slice_chars(1..3), "\u{1f603}\u{1f604}");
+    /// ```
+    pub fn slice_chars(&self, range: impl RangeBounds<usize>) -> Self {
+        self.try_slice_chars(range).unwrap()
+    }
+
+    /// Create a subslice of this string, using char indices instead of byte indices.
+    ///
+    /// The range is translated into byte indices by counting chars, which is an *O(n)*
+    /// operation. Returns an error if the range is out of bounds or the end is before the start.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// # use imstr::error::SliceError;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.try_slice_chars(1..=2).unwrap(), "\u{e9}l");
+    /// assert_eq!(string.try_slice_chars(..10), Err(SliceError::EndOutOfBounds));
+    /// ```
+    pub fn try_slice_chars(&self, range: impl RangeBounds<usize>) -> Result<Self, SliceError> {
+        self.try_slice_by(range, |index| self.byte_index_of_char(index))
+    }
+
+    /// Returns the `char` at the given char index.
+    ///
+    /// The index counts `char`s rather than bytes, which makes this an *O(n)* operation. Returns
+    /// `None` if the index is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.char_at(1), Some('\u{e9}'));
+    /// assert_eq!(string.char_at(5), None);
+    /// ```
+    pub fn char_at(&self, char_index: usize) -> Option<char> {
+        self.chars().nth(char_index)
+    }
+
+    /// Translates a char index into a byte index.
+    ///
+    /// Returns the byte index at which the `char` with the given index starts. An index equal to
+    /// the number of `char`s in this string is translated into the length of this string, since
+    /// it is a valid position at the end of it. Returns `None` if the index is larger than that.
+    /// This is an *O(n)* operation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.byte_index_of_char(2), Some(3));
+    /// assert_eq!(string.byte_index_of_char(5), Some(6));
+    /// assert_eq!(string.byte_index_of_char(6), None);
+    /// ```
+    pub fn byte_index_of_char(&self, char_index: usize) -> Option<usize> {
+        self.char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(self.len()))
+            .nth(char_index)
+    }
+
+    /// Create a subslice of this string, using extended grapheme cluster indices instead of byte
+    /// indices.
+    ///
+    /// This will panic if the specified range is invalid. Use the
+    /// [try_slice_graphemes](ImString::try_slice_graphemes) method if you want to handle invalid
+    /// ranges. This method is only available with the `unicode` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("no\u{308}el");
+    /// assert_eq!(string.slice_graphemes(1..2), "o\u{308}");
+    /// ```
+    #[cfg(feature = "unicode")]
+    pub fn slice_graphemes(&self, range: impl RangeBounds<usize>) -> Self {
+        self.try_slice_graphemes(range).unwrap()
+    }
+
+    /// Create a subslice of this string, using extended grapheme cluster indices instead of byte
+    /// indices.
+    ///
+    /// Returns an error if the range is out of bounds or the end is before the start. This method
+    /// is only available with the `unicode` feature enabled.
+    #[cfg(feature = "unicode")]
+    pub fn try_slice_graphemes(&self, range: impl RangeBounds<usize>) -> Result<Self, SliceError> {
+        use unicode_segmentation::UnicodeSegmentation;
+        self.try_slice_by(range, |index| {
+            self.as_str()
+                .grapheme_indices(true)
+                .map(|(index, _)| index)
+                .chain(std::iter::once(self.len()))
+                .nth(index)
+        })
+    }
+
+    /// Finds the closest char boundary at or before `index`.
+    ///
+    /// If `index` is past the end of this string, this returns the length of this string. If
+    /// `index` is already a char boundary, it is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a\u{1f600}b");
+    /// assert_eq!(string.floor_char_boundary(3), 1);
+    /// assert_eq!(string.floor_char_boundary(5), 5);
+    /// assert_eq!(string.floor_char_boundary(100), 6);
+    /// ```
+    pub fn floor_char_boundary(&self, index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        (0..=index)
+            .rev()
+            .find(|index| self.as_str().is_char_boundary(*index))
+            .unwrap_or(0)
+    }
+
+    /// Finds the closest char boundary at or after `index`.
+    ///
+    /// If `index` is past the end of this string, this returns the length of this string. If
+    /// `index` is already a char boundary, it is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a\u{1f600}b");
+    /// assert_eq!(string.ceil_char_boundary(3), 5);
+    /// assert_eq!(string.ceil_char_boundary(1), 1);
+    /// assert_eq!(string.ceil_char_boundary(100), 6);
+    /// ```
+    pub fn ceil_char_boundary(&self, index: usize) -> usize {
+        if index >= self.len() {
+            return self.len();
+        }
+        (index..self.len())
+            .find(|index| self.as_str().is_char_boundary(*index))
+            .unwrap_or(self.len())
+    }
+
+    /// Returns a subslice of this string, or `None` if the range is invalid.
+    ///
+    /// This works the same way as [`str::get`], except that it returns an `ImString` which shares
+    /// the underlying data with this string. Use [try_slice](ImString::try_slice) if you need to
+    /// know why the range is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// assert_eq!(string.get(3..), Some(ImString::from("llo")));
+    /// assert_eq!(string.get(2..), None);
+    /// assert_eq!(string.get(..10), None);
+    /// ```
+    pub fn get(&self, range: impl RangeBounds<usize>) -> Option<Self> {
+        self.try_slice(range).ok()
+    }
+
+    /// Returns a copy of this string with each char escaped using [`char::escape_debug`].
+    ///
+    /// This works the same way as [`str::escape_debug`], except that it collects the result into
+    /// a new `ImString`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("\"hi\"\n\u{e4}");
+    /// assert_eq!(string.escape_debug(), "\\\"hi\\\"\\n\u{e4}");
+    /// ```
+    pub fn escape_debug(&self) -> Self {
+        ImString::from_std_string(self.as_str().escape_debug().to_string())
+    }
+
+    /// Returns a copy of this string with each char escaped using [`char::escape_default`].
+    ///
+    /// This works the same way as [`str::escape_default`], except that it collects the result
+    /// into a new `ImString`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("\"hi\"\n\u{e4}");
+    /// assert_eq!(string.escape_default(), "\\\"hi\\\"\\n\\u{e4}");
+    /// ```
+    pub fn escape_default(&self) -> Self {
+        ImString::from_std_string(self.as_str().escape_default().to_string())
+    }
+
+    /// Returns a copy of this string with each char escaped using [`char::escape_unicode`].
+    ///
+    /// This works the same way as [`str::escape_unicode`], except that it collects the result
+    /// into a new `ImString`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a\n");
+    /// assert_eq!(string.escape_unicode(), "\\u{61}\\u{a}");
+    /// ```
+    pub fn escape_unicode(&self) -> Self {
+        ImString::from_std_string(self.as_str().escape_unicode().to_string())
+    }
+
+    /// Parses this string into another type.
+    ///
+    /// This works the same way as [`str::parse`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("42");
+    /// assert_eq!(string.parse::<u32>(), Ok(42));
+    /// assert!(string.parse::<bool>().is_err());
+    /// ```
+    pub fn parse<F: FromStr>(&self) -> Result<F, F::Err> {
+        self.as_str().parse()
+    }
+
+    /// An iterator over the substrings of this string, separated by `pattern`, without a trailing
+    /// empty substring.
+    ///
+    /// This works the same way as [`str::split_terminator`], except that it returns ImString
+    /// instances which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a;b;c;");
+    /// let parts: Vec<ImString> = string.split_terminator(";").collect();
+    /// assert_eq!(parts, ["a", "b", "c"]);
+    /// ```
+    pub fn split_terminator<'a>(&'a self, pattern: &'a str) -> SplitTerminator<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().split_terminator(pattern))
+    }
+
+    /// An iterator over the substrings of this string, separated by `pattern`, without a trailing
+    /// empty substring, in reverse order.
+    ///
+    /// This works the same way as [`str::rsplit_terminator`], except that it returns ImString
+    /// instances which share the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a;b;c;");
+    /// let parts: Vec<ImString> = string.rsplit_terminator(";").collect();
+    /// assert_eq!(parts, ["c", "b", "a"]);
+    /// ```
+    pub fn rsplit_terminator<'a>(&'a self, pattern: &'a str) -> RSplitTerminator<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().rsplit_terminator(pattern))
+    }
+
+    /// An iterator over the substrings of this string, each ending with `pattern`.
+    ///
+    /// This works the same way as [`str::split_inclusive`], except that it returns ImString
+    /// instances which share the underlying data with this string. The last substring does not
+    /// end with `pattern` if this string does not.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("a;b;c");
+    /// let parts: Vec<ImString> = string.split_inclusive(";").collect();
+    /// assert_eq!(parts, ["a;", "b;", "c"]);
+    /// ```
+    pub fn split_inclusive<'a>(&'a self, pattern: &'a str) -> SplitInclusive<'a, S> {
+        ImStringIterator::new(&self.string, self.as_str().split_inclusive(pattern))
+    }
+
+    /// An iterator over the lines of a string, including their line endings.
+    ///
+    /// Lines are split after each newline (`\n`), which is kept at the end of the line along with
+    /// a preceding carriage return (`\r`), if any. The last line is returned even if it does not
+    /// end with a newline. Concatenating all lines yields the original string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("one\ntwo\r\nthree");
+    /// let lines: Vec<ImString> = string.lines_with_terminators().collect();
+    /// assert_eq!(lines, ["one\n", "two\r\n", "three"]);
+    /// ```
+    pub fn lines_with_terminators(&self) -> LinesWithTerminators<'_, S> {
+        ImStringIterator::new(&self.string, self.as_str().split_inclusive('\n'))
+    }
+
+    /// Appends the contents of another string to the end of this string.
+    ///
+    /// If this is the only reference to the underlying data, the contents are appended to it
+    /// directly. Otherwise, the contents of both strings are copied into a new buffer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let mut string = ImString::from("hello");
+    /// let other = ImString::from("big world");
+    /// string.append(&other.slice(3..));
+    /// assert_eq!(string, "hello world");
+    /// ```
+    pub fn append(&mut self, other: &ImString<S>) {
+        unsafe {
+            self.unchecked_append(other.len(), |mut string| {
+                string.push_str(other.as_str());
+                string
+            });
+        }
+    }
+
+    /// Copies the contents of this string into a new thread-safe `ImString`.
+    ///
+    /// This is a shorthand for [`clone_as::<Threadsafe>()`](ImString::clone_as). The returned
+    /// string does not share any data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::string::{ImString, Local};
+    ///
+    /// let local: ImString<Local> = ImString::from("hello world");
+    /// let threadsafe = local.slice(6..).to_threadsafe();
+    /// std::thread::spawn(move || assert_eq!(threadsafe, "world")).join().unwrap();
+    /// ```
+    pub fn to_threadsafe(&self) -> ImString<Threadsafe> {
+        self.clone_as()
+    }
+
+    /// Copies the contents of this string into a new non-thread-safe `ImString`.
+    ///
+    /// This is a shorthand for [`clone_as::<Local>()`](ImString::clone_as). The returned string
+    /// does not share any data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello world");
+    /// let local = string.slice(..5).to_local();
+    /// assert_eq!(local, "hello");
+    /// ```
+    pub fn to_local(&self) -> ImString<Local> {
+        self.clone_as()
+    }
+
+    /// Returns a reader over the bytes of this string.
+    ///
+    /// The reader holds a clone of the underlying data, so it keeps it alive without borrowing this
+    /// string. It also implements [`BufRead`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// use std::io::Read;
+    ///
+    /// let string = ImString::from("hello world");
+    /// let mut reader = string.reader();
+    /// let mut buffer = [0; 5];
+    /// assert_eq!(reader.read(&mut buffer).unwrap(), 5);
+    /// assert_eq!(&buffer, b"hello");
+    /// ```
+    pub fn reader(&self) -> ImStringReader<S> {
+        ImStringReader {
+            string: self.string.clone(),
+            offset: self.offset.clone(),
+        }
+    }
+
+    /// Returns the leftmost-first match of the regular expression in this string.
+    ///
+    /// The match is returned as an `ImString` which shares the underlying data with this string.
+    /// This method is only available with the `regex` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// use regex::Regex;
+    ///
+    /// let string = ImString::from("version 1.23.4");
+    /// let regex = Regex::new(r"\d+\.\d+\.\d+").unwrap();
+    /// assert_eq!(string.regex_find(&regex).unwrap(), "1.23.4");
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn regex_find(&self, regex: &regex::Regex) -> Option<Self> {
+        regex
+            .find(self.as_str())
+            .map(|found| self.str_ref(found.as_str()))
+    }
+
+    /// Returns the capture groups of the leftmost-first match of the regular expression in this
+    /// string.
+    ///
+    /// The returned vector contains one entry for every capture group of the regular expression,
+    /// the first one being the entire match. Groups which did not participate in the match are
+    /// `None`. Names of the capture groups can be mapped to indices using
+    /// [`Regex::capture_names`](regex::Regex::capture_names). Every group is returned as an
+    /// `ImString` which shares the underlying data with this string.
+    ///
+    /// This method is only available with the `regex` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// use regex::Regex;
+    ///
+    /// let string = ImString::from("key=value");
+    /// let regex = Regex::new(r"(\w+)=(\w+)(;)?").unwrap();
+    /// let captures = string.regex_captures(&regex).unwrap();
+    /// assert_eq!(captures[1].as_ref().unwrap(), "key");
+    /// assert_eq!(captures[2].as_ref().unwrap(), "value");
+    /// assert_eq!(captures[3], None);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn regex_captures(&self, regex: &regex::Regex) -> Option<Vec<Option<Self>>> {
+        regex.captures(self.as_str()).map(|captures| {
+            captures
+                .iter()
+                .map(|group| group.map(|group| self.str_ref(group.as_str())))
+                .collect()
+        })
+    }
+
+    /// Copies the contents of this string into a [`Bytes`](bytes::Bytes) buffer.
+    ///
+    /// This method is only available with the `bytes` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("hello, world");
+    /// let bytes = string.slice(7..).to_bytes();
+    /// assert_eq!(bytes, "world");
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn to_bytes(&self) -> bytes::Bytes {
+        bytes::Bytes::copy_from_slice(self.as_bytes())
+    }
+
+    /// Converts a [`Bytes`](bytes::Bytes) buffer into an `ImString`.
+    ///
+    /// Returns an error if the buffer is not valid UTF-8. If the buffer is the only reference to
+    /// its data, the allocation is reused, otherwise the contents are copied. This method is only
+    /// available with the `bytes` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// use bytes::Bytes;
+    ///
+    /// let string = ImString::try_from_bytes(Bytes::from("h\u{e9}llo")).unwrap();
+    /// assert_eq!(string, "h\u{e9}llo");
+    /// assert!(ImString::try_from_bytes(Bytes::from_static(b"\xF0\x90")).is_err());
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn try_from_bytes(bytes: bytes::Bytes) -> Result<Self, FromUtf8Error> {
+        ImString::from_utf8(bytes.into())
+    }
+
+    /// Returns the longest prefix this string shares with `other`.
+    ///
+    /// The comparison is done per character, so the returned prefix always ends on a character
+    /// boundary, even if the strings diverge in the middle of a multi-byte character. The
+    /// returned `ImString` shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("interstellar");
+    /// assert_eq!(string.common_prefix("internet"), "inter");
+    /// assert_eq!(string.common_prefix("stellar"), "");
+    /// ```
+    pub fn common_prefix(&self, other: &str) -> Self {
+        let length: usize = self
+            .chars()
+            .zip(other.chars())
+            .take_while(|(left, right)| left == right)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        self.slice(..length)
+    }
+
+    /// Returns the longest suffix this string shares with `other`.
+    ///
+    /// The comparison is done per character, so the returned suffix always starts on a character
+    /// boundary, even if the strings diverge in the middle of a multi-byte character. The
+    /// returned `ImString` shares the underlying data with this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("interstellar");
+    /// assert_eq!(string.common_suffix("cellar"), "ellar");
+    /// assert_eq!(string.common_suffix("internet"), "");
+    /// ```
+    pub fn common_suffix(&self, other: &str) -> Self {
+        let length: usize = self
+            .chars()
+            .rev()
+            .zip(other.chars().rev())
+            .take_while(|(left, right)| left == right)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        self.slice(self.len() - length..)
+    }
+
+    /// Returns an owning iterator over the chars of this string.
+    ///
+    /// This works like [`str::chars`], except that the iterator holds a cheap clone of this
+    /// string rather than borrowing it, so it can outlive this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// fn chars() -> impl Iterator<Item = char> {
+    ///     let string = ImString::from("h\u{e9}llo");
+    ///     string.chars_owned()
+    /// }
+    ///
+    /// assert_eq!(chars().collect::<Vec<_>>(), ['h', '\u{e9}', 'l', 'l', 'o']);
+    /// ```
+    pub fn chars_owned(&self) -> IntoChars<S> {
+        self.clone().into_iter()
+    }
+
+    /// Returns an owning iterator over the chars of this string and their byte positions.
+    ///
+    /// This works like [`str::char_indices`], except that the iterator holds a cheap clone of
+    /// this string rather than borrowing it, so it can outlive this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}llo");
+    /// let indices: Vec<_> = string.char_indices_owned().take(3).collect();
+    /// assert_eq!(indices, [(0, 'h'), (1, '\u{e9}'), (3, 'l')]);
+    /// ```
+    pub fn char_indices_owned(&self) -> IntoCharIndices<S> {
+        IntoCharIndices {
+            start: self.offset.start,
+            chars: self.chars_owned(),
+        }
+    }
+
+    /// Returns an owning iterator over the bytes of this string.
+    ///
+    /// This works like [`str::bytes`], except that the iterator holds a cheap clone of this
+    /// string rather than borrowing it, so it can outlive this string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// let string = ImString::from("h\u{e9}");
+    /// let bytes: Vec<u8> = string.bytes_owned().collect();
+    /// assert_eq!(bytes, [0x68, 0xc3, 0xa9]);
+    /// ```
+    pub fn bytes_owned(&self) -> IntoBytes<S> {
+        IntoBytes {
+            string: self.string.clone(),
+            offset: self.offset.clone(),
+        }
+    }
+
+    /// Returns `true` if `other` is a substring of this string.
+    ///
+    /// This works the same way as [`str::contains`], but takes an `ImString` with any storage
+    /// type, so that it can be called without converting it to a string slice first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::string::{ImString, Local};
+    ///
+    /// let string: ImString = ImString::from("hello world");
+    /// let other: ImString<Local> = ImString::from("lo wo");
+    /// assert!(string.contains_imstr(&other));
+    /// ```
+    pub fn contains_imstr<O: Data<String>>(&self, other: &ImString<O>) -> bool {
+        self.as_str().contains(other.as_str())
+    }
+
+    /// Returns `true` if this string starts with `other`.
+    ///
+    /// This works the same way as [`str::starts_with`], but takes an `ImString` with any storage
+    /// type, so that it can be called without converting it to a string slice first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::string::{ImString, Local};
+    ///
+    /// let string: ImString = ImString::from("hello world");
+    /// let other: ImString<Local> = ImString::from("hello");
+    /// assert!(string.starts_with_imstr(&other));
+    /// ```
+    pub fn starts_with_imstr<O: Data<String>>(&self, other: &ImString<O>) -> bool {
+        self.as_str().starts_with(other.as_str())
+    }
+
+    /// Returns `true` if this string ends with `other`.
+    ///
+    /// This works the same way as [`str::ends_with`], but takes an `ImString` with any storage
+    /// type, so that it can be called without converting it to a string slice first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::string::{ImString, Local};
+    ///
+    /// let string: ImString = ImString::from("hello world");
+    /// let other: ImString<Local> = ImString::from("world");
+    /// assert!(string.ends_with_imstr(&other));
+    /// ```
+    pub fn ends_with_imstr<O: Data<String>>(&self, other: &ImString<O>) -> bool {
+        self.as_str().ends_with(other.as_str())
+    }
+}
+
+impl ImString<Threadsafe> {
+    /// Creates an `ImString` from an already shared [`String`] without copying it.
+    ///
+    /// This is the inverse of [`raw_string()`](ImString::raw_string). The returned string refers
+    /// to the entire contents of the `String`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use imstr::ImString;
+    /// use std::sync::Arc;
+    ///
+    /// let shared = Arc::new(String::from("hello world"));
+    /// let first = ImString::from_arc(shared.clone());
+    /// let second = ImString::from_arc(shared);
+    /// assert_eq!(first.ref_count(), 2);
+    /// assert_eq!(first, second);
+    /// ```
+    pub fn from_arc(string: Arc<String>) -> Self {
+        ImString {
+            offset: 0..string.len(),
+            string,
+        }
+    }
+}
+
+impl ImString<Local> {
+    /// Creates an `ImString` from an already shared [`String`] without copying it.
+    ///
+    /// This is the inverse of [`raw_string()`](ImString::raw_string). The returned string refers
+    /// to the entire contents of the `String`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::string::{ImString, Local};
+    /// use std::rc::Rc;
+    ///
+    /// let shared = Rc::new(String::from("hello world"));
+    /// let string: ImString<Local> = ImString::from_rc(shared.clone());
+    /// assert_eq!(string, "hello world");
+    /// assert_eq!(Rc::strong_count(&shared), 2);
+    /// ```
+    pub fn from_rc(string: Rc<String>) -> Self {
+        ImString {
+            offset: 0..string.len(),
+            string,
+        }
+    }
+}
+
+impl ImString<Static> {
+    /// Creates an `ImString` from a static string without allocating.
+    ///
+    /// The returned string refers to the static data directly, cloning and slicing it does not
+    /// allocate either. Modifying it copies the data into a newly allocated backing string. See
+    /// [`Static`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imstr::data::Static;
+    /// use imstr::string::ImString;
+    ///
+    /// const GREETING: ImString<Static> = ImString::from_static("hello world");
+    /// let world = GREETING.slice(6..);
+    /// assert_eq!(world, "world");
+    /// assert_eq!(world.as_ptr(), GREETING[6..].as_ptr());
+    /// ```
+    pub const fn from_static(string: &'static str) -> Self {
+        ImString {
+            offset: 0..string.len(),
+            string: Static::from_static(string),
+        }
+    }
+}
+
+impl<S: Data<String>> Default for ImString<S> {
+    fn default() -> Self {
+        ImString::new()
+    }
+}
+
+impl<S: Data<String>> From<&str> for ImString<S> {
+    fn from(string: &str) -> Self {
+        ImString {
+            offset: 0..string.len(),
+            string: S::new_borrowed(string),
+        }
+    }
+}
+
+impl<S: Data<String>> From<char> for ImString<S> {
+    fn from(c: char) -> Self {
+        String::from(c).into()
+    }
+}
+
+impl<S: Data<String>> From<String> for ImString<S> {
+    fn from(string: String) -> Self {
+        ImString::from_std_string(string)
+    }
+}
+
+impl<'a, S: Data<String>> From<Cow<'a, str>> for ImString<S> {
+    fn from(string: Cow<'a, str>) -> Self {
+        ImString::from(string.into_owned())
+    }
+}
+
+impl<S: Data<String>> From<&String> for ImString<S> {
+    fn from(string: &String) -> Self {
+        ImString::from(string.as_str())
+    }
+}
+
+impl<S: Data<String>> From<Box<str>> for ImString<S> {
+    fn from(string: Box<str>) -> Self {
+        ImString::from_std_string(String::from(string))
+    }
+}
+
+impl<S: Data<String>> From<Vec<char>> for ImString<S> {
+    fn from(chars: Vec<char>) -> Self {
+        ImString::from_std_string(chars.into_iter().collect())
+    }
+}
+
+impl<S: Data<String>> From<ImString<S>> for String {
+    fn from(string: ImString<S>) -> Self {
+        string.into_std_string()
+    }
+}
+
+impl<S: Data<String>> TryFrom<&[u8]> for ImString<S> {
+    type Error = FromUtf8Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        ImString::from_utf8(bytes.to_vec())
+    }
+}
+
+impl<S: Data<String>> TryFrom<Vec<u8>> for ImString<S> {
+    type Error = FromUtf8Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        ImString::from_utf8(bytes)
+    }
+}
+
+impl<'a, S: Data<String>> TryFrom<&'a Path> for ImString<S> {
+    type Error = &'a Path;
+
+    fn try_from(path: &'a Path) -> Result<Self, Self::Error> {
+        path.to_str().map(ImString::from).ok_or(path)
+    }
+}
+
+impl<S: Data<String>> TryFrom<PathBuf> for ImString<S> {
+    type Error = PathBuf;
+
+    fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
+        match path.into_os_string().into_string() {
+            Ok(string) => Ok(ImString::from_std_string(string)),
+            Err(string) => Err(string.into()),
+        }
+    }
+}
+
+impl<S: Data<String>> From<ImString<S>> for PathBuf {
+    fn from(string: ImString<S>) -> Self {
+        string.into_std_string().into()
+    }
+}
+
+impl<S: Data<String>> From<ImString<S>> for Box<str> {
+    fn from(string: ImString<S>) -> Self {
+        string.into_std_string().into_boxed_str()
+    }
+}
+
+impl<S: Data<String>> From<ImString<S>> for Arc<str> {
+    fn from(string: ImString<S>) -> Self {
+        Arc::from(string.as_str())
+    }
+}
+
+impl<S: Data<String>> From<ImString<S>> for Rc<str> {
+    fn from(string: ImString<S>) -> Self {
+        Rc::from(string.as_str())
+    }
+}
+
+impl<S: Data<String>> From<ImString<S>> for Vec<u8> {
+    fn from(string: ImString<S>) -> Self {
+        string.into_bytes()
+    }
+}
+
+impl<S: Data<String>> PartialEq<str> for ImString<S> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str().eq(other)
+    }
+}
+
+impl<'a, S: Data<String>> PartialEq<&'a str> for ImString<S> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str().eq(*other)
+    }
+}
+
+impl<S: Data<String>> PartialEq<String> for ImString<S> {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str().eq(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialEq<[u8]> for ImString<S> {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes().eq(other)
+    }
+}
+
+impl<'a, S: Data<String>> PartialEq<&'a [u8]> for ImString<S> {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.as_bytes().eq(*other)
+    }
+}
+
+impl<S: Data<String>> PartialEq<Vec<u8>> for ImString<S> {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.as_bytes().eq(other.as_slice())
+    }
+}
+
+impl<S: Data<String>, O: Data<String>> PartialEq<ImString<O>> for ImString<S> {
+    fn eq(&self, other: &ImString<O>) -> bool {
+        self.as_str().eq(other.as_str())
+    }
+}
+
+impl<S: Data<String>> Eq for ImString<S> {}
+
+impl<S: Data<String>> PartialOrd<ImString<S>> for ImString<S> {
+    fn partial_cmp(&self, other: &ImString<S>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: Data<String>> PartialEq<ImString<S>> for str {
+    fn eq(&self, other: &ImString<S>) -> bool {
+        self.eq(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialEq<ImString<S>> for &str {
+    fn eq(&self, other: &ImString<S>) -> bool {
+        (*self).eq(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialEq<ImString<S>> for String {
+    fn eq(&self, other: &ImString<S>) -> bool {
+        self.as_str().eq(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialOrd<str> for ImString<S> {
+    fn partial_cmp(&self, other: &str) -> Option<Ordering> {
+        self.as_str().partial_cmp(other)
+    }
+}
+
+impl<'a, S: Data<String>> PartialOrd<&'a str> for ImString<S> {
+    fn partial_cmp(&self, other: &&'a str) -> Option<Ordering> {
+        self.as_str().partial_cmp(*other)
+    }
+}
+
+impl<S: Data<String>> PartialOrd<String> for ImString<S> {
+    fn partial_cmp(&self, other: &String) -> Option<Ordering> {
+        self.as_str().partial_cmp(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialOrd<ImString<S>> for str {
+    fn partial_cmp(&self, other: &ImString<S>) -> Option<Ordering> {
+        self.partial_cmp(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialOrd<ImString<S>> for &str {
+    fn partial_cmp(&self, other: &ImString<S>) -> Option<Ordering> {
+        (*self).partial_cmp(other.as_str())
+    }
+}
+
+impl<S: Data<String>> PartialOrd<ImString<S>> for String {
+    fn partial_cmp(&self, other: &ImString<S>) -> Option<Ordering> {
+        self.as_str().partial_cmp(other.as_str())
+    }
+}
+
+impl<S: Data<String>> Ord for ImString<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<S: Data<String>> Debug for ImString<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<S: Data<String>> Display for ImString<S> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), FmtError> {
+        Display::fmt(self.as_str(), formatter)
+    }
+}
+
+impl<S: Data<String>> FromStr for ImString<S> {
+    type Err = Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ImString::from(s))
+    }
+}
+
+// Delegate hash to contained str. This is important!
+impl<S: Data<String>> Hash for ImString<S> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.as_str().hash(hasher)
+    }
+}
+
+impl<S: Data<String>> Write for ImString<S> {
+    fn write_str(&mut self, string: &str) -> Result<(), FmtError> {
+        self.push_str(string);
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> Result<(), FmtError> {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<S: Data<String>> Index<Range<usize>> for ImString<S> {
+    type Output = str;
+    fn index(&self, index: Range<usize>) -> &str {
+        &self.as_str()[index]
+    }
+}
+
+impl<S: Data<String>> Index<RangeFrom<usize>> for ImString<S> {
+    type Output = str;
+    fn index(&self, index: RangeFrom<usize>) -> &str {
+        &self.as_str()[index]
+    }
+}
+
+impl<S: Data<String>> Index<RangeFull> for ImString<S> {
+    type Output = str;
+    fn index(&self, _index: RangeFull) -> &str {
+        self.as_str()
+    }
+}
+
+impl<S: Data<String>> Index<RangeInclusive<usize>> for ImString<S> {
+    type Output = str;
+    fn index(&self, index: RangeInclusive<usize>) -> &str {
+        &self.as_str()[index]
+    }
+}
+
+impl<S: Data<String>> Index<RangeTo<usize>> for ImString<S> {
+    type Output = str;
+    fn index(&self, index: RangeTo<usize>) -> &str {
+        &self.as_str()[index]
+    }
+}
+
+pub type Lines<'a, S> = ImStringIterator<'a, S, std::str::Lines<'a>>;
+
+/// Iterator returned by [`ImString::lines_with_terminators`].
+pub type LinesWithTerminators<'a, S> = ImStringIterator<'a, S, std::str::SplitInclusive<'a, char>>;
+
+/// Iterator returned by [`ImString::split`].
+pub type Split<'a, S> = ImStringIterator<'a, S, std::str::Split<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::rsplit`].
+pub type RSplit<'a, S> = ImStringIterator<'a, S, std::str::RSplit<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::splitn`].
+pub type SplitN<'a, S> = ImStringIterator<'a, S, std::str::SplitN<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::rsplitn`].
+pub type RSplitN<'a, S> = ImStringIterator<'a, S, std::str::RSplitN<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::split_terminator`].
+pub type SplitTerminator<'a, S> = ImStringIterator<'a, S, std::str::SplitTerminator<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::rsplit_terminator`].
+pub type RSplitTerminator<'a, S> = ImStringIterator<'a, S, std::str::RSplitTerminator<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::split_inclusive`].
+pub type SplitInclusive<'a, S> = ImStringIterator<'a, S, std::str::SplitInclusive<'a, &'a str>>;
+
+/// Iterator returned by [`ImString::split_whitespace`].
+pub type SplitWhitespace<'a, S> = ImStringIterator<'a, S, std::str::SplitWhitespace<'a>>;
+
+/// Iterator returned by [`ImString::split_ascii_whitespace`].
+pub type SplitAsciiWhitespace<'a, S> = ImStringIterator<'a, S, std::str::SplitAsciiWhitespace<'a>>;
+
+/// Iterator returned by [`ImString::graphemes`].
+#[cfg(feature = "unicode")]
+pub type Graphemes<'a, S> = ImStringIterator<'a, S, unicode_segmentation::Graphemes<'a>>;
+
+/// Iterator returned by [`ImString::matches`].
+pub type Matches<'a, S> = ImStringIterator<'a, S, std::str::Matches<'a, &'a str>>;
+
+pub struct ImStringIterator<'a, S: Data<String>, I: Iterator<Item = &'a str>> {
+    string: &'a S,
+    iterator: I,
+}
+
+impl<'a, S: Data<String>, I: Iterator<Item = &'a str>> Iterator for ImStringIterator<'a, S, I> {
+    type Item = ImString<S>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|slice| self.wrap(slice))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a, S: Data<String>, I: FusedIterator<Item = &'a str>> FusedIterator
+    for ImStringIterator<'a, S, I>
+{
+}
+
+impl<'a, S: Data<String>, I: DoubleEndedIterator<Item = &'a str>> DoubleEndedIterator
+    for ImStringIterator<'a, S, I>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iterator.next_back().map(|slice| self.wrap(slice))
+    }
+}
+
+impl<'a, S: Data<String>, I: Iterator<Item = &'a str>> ImStringIterator<'a, S, I> {
+    fn new(string: &'a S, iterator: I) -> Self {
+        ImStringIterator { string, iterator }
+    }
+
+    fn wrap(&self, slice: &str) -> ImString<S> {
+        let offset =
+            try_slice_offset(self.string.get_borrowed().as_bytes(), slice.as_bytes()).unwrap();
+        ImString {
+            string: self.string.clone(),
+            offset,
+        }
+    }
+}
+
+impl<S: Data<String>> Deref for ImString<S> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<S: Data<String>> Borrow<str> for ImString<S> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<S: Data<String>> AsRef<str> for ImString<S> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<S: Data<String>> AsRef<Path> for ImString<S> {
+    fn as_ref(&self) -> &Path {
+        self.as_str().as_ref()
+    }
+}
+
+impl<S: Data<String>> AsRef<OsStr> for ImString<S> {
+    fn as_ref(&self) -> &OsStr {
+        self.as_str().as_ref()
+    }
+}
+
+impl<S: Data<String>> AsRef<[u8]> for ImString<S> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_ref()
+    }
+}
+
+impl<S: Data<String>> ToSocketAddrs for ImString<S> {
+    type Iter = <String as ToSocketAddrs>::Iter;
+    fn to_socket_addrs(&self) -> std::io::Result<<String as ToSocketAddrs>::Iter> {
+        self.as_str().to_socket_addrs()
+    }
+}
+
+impl<S: Data<String>> Add<&str> for ImString<S> {
+    type Output = ImString<S>;
+    fn add(mut self, string: &str) -> Self::Output {
+        self.push_str(string);
+        self
+    }
+}
+
+impl<S: Data<String>> AddAssign<&str> for ImString<S> {
+    fn add_assign(&mut self, string: &str) {
+        self.push_str(string);
+    }
+}
+
+impl<S: Data<String>> Add<ImString<S>> for ImString<S> {
+    type Output = ImString<S>;
+    fn add(mut self, other: ImString<S>) -> Self::Output {
+        self.push_str(other.as_str());
+        self
+    }
+}
+
+impl<S: Data<String>> Add<String> for ImString<S> {
+    type Output = ImString<S>;
+    fn add(mut self, string: String) -> Self::Output {
+        self.push_str(&string);
+        self
+    }
+}
+
+impl<S: Data<String>> Add<char> for ImString<S> {
+    type Output = ImString<S>;
+    fn add(mut self, c: char) -> Self::Output {
+        self.push(c);
+        self
+    }
+}
+
+impl<S: Data<String>> AddAssign<String> for ImString<S> {
+    fn add_assign(&mut self, string: String) {
+        self.push_str(&string);
+    }
+}
+
+impl<S: Data<String>> AddAssign<char> for ImString<S> {
+    fn add_assign(&mut self, c: char) {
+        self.push(c);
+    }
+}
+
+impl<S: Data<String>> AddAssign<ImString<S>> for ImString<S> {
+    fn add_assign(&mut self, other: ImString<S>) {
+        self.append(&other);
+    }
+}
+
+impl<S: Data<String>> Extend<char> for ImString<S> {
+    fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.extend(iter);
+                string
+            });
+        }
+    }
+}
+
+impl<'a, S: Data<String>> Extend<&'a char> for ImString<S> {
+    fn extend<T: IntoIterator<Item = &'a char>>(&mut self, iter: T) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.extend(iter);
+                string
+            });
+        }
+    }
+}
+
+impl<'a, S: Data<String>> Extend<&'a str> for ImString<S> {
+    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.extend(iter);
+                string
+            });
+        }
+    }
+}
+
+impl<S: Data<String>> Extend<String> for ImString<S> {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, iter: T) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.extend(iter);
+                string
+            });
+        }
+    }
+}
+
+impl<'a, S: Data<String>> Extend<Cow<'a, str>> for ImString<S> {
+    fn extend<T: IntoIterator<Item = Cow<'a, str>>>(&mut self, iter: T) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                string.extend(iter);
+                string
+            });
+        }
+    }
+}
+
+impl<S: Data<String>> Extend<ImString<S>> for ImString<S> {
+    fn extend<T: IntoIterator<Item = ImString<S>>>(&mut self, iter: T) {
+        unsafe {
+            self.unchecked_append(0, |mut string| {
+                for piece in iter {
+                    string.push_str(piece.as_str());
+                }
+                string
+            });
+        }
+    }
+}
+
+impl<S: Data<String>> FromIterator<char> for ImString<S> {
+    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+        let mut string = ImString::new();
+        string.extend(iter);
+        string
+    }
+}
+
+impl<'a, S: Data<String>> FromIterator<&'a char> for ImString<S> {
+    fn from_iter<T: IntoIterator<Item = &'a char>>(iter: T) -> Self {
+        let mut string = ImString::new();
+        string.extend(iter);
+        string
+    }
+}
+
+impl<'a, S: Data<String>> FromIterator<&'a str> for ImString<S> {
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
+        let mut string = ImString::new();
+        string.extend(iter);
+        string
+    }
+}
+
+impl<S: Data<String>> FromIterator<String> for ImString<S> {
+    fn from_iter<T: IntoIterator<Item = String>>(iter: T) -> Self {
+        let mut string = ImString::new();
+        string.extend(iter);
+        string
+    }
+}
+
+impl<S: Data<String>> FromIterator<ImString<S>> for ImString<S> {
+    fn from_iter<T: IntoIterator<Item = ImString<S>>>(iter: T) -> Self {
+        let mut string = ImString::new();
+        string.extend(iter);
+        string
+    }
+}
+
+impl<S: Data<String>> Sum<ImString<S>> for ImString<S> {
+    fn sum<I: Iterator<Item = ImString<S>>>(iter: I) -> Self {
+        iter.collect()
+    }
+}
+
+// Generates strings which are sliced at random char boundaries, so that the offset is not
+// always the entire backing string.
+#[cfg(feature = "arbitrary")]
+impl<'a, S: Data<String>> arbitrary::Arbitrary<'a> for ImString<S> {
+    fn arbitrary(input: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let string = ImString::from_std_string(String::arbitrary(input)?);
+        let start = string.floor_char_boundary(input.int_in_range(0..=string.len())?);
+        let end = string.ceil_char_boundary(input.int_in_range(start..=string.len())?);
+        Ok(unsafe { string.slice_unchecked(start..end) })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        arbitrary::size_hint::and(
+            <String as arbitrary::Arbitrary>::size_hint(depth),
+            (0, Some(2 * std::mem::size_of::<usize>())),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: Data<String>> serde::Serialize for ImString<S> {
+    fn serialize<T: serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+struct ImStringVisitor<S: Data<String>>(std::marker::PhantomData<S>);
+
+#[cfg(feature = "serde")]
+impl<'de, S: Data<String>> serde::de::Visitor<'de> for ImStringVisitor<S> {
+    type Value = ImString<S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> Result<(), FmtError> {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(ImString::from(value))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(ImString::from_std_string(value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S: Data<String>> serde::Deserialize<'de> for ImString<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_string(ImStringVisitor(std::marker::PhantomData))
+    }
+}
+
+/// Iterator returned by [`ImString::drain`].
+pub type Drain<S> = IntoChars<S>;
+
+/// Owning iterator over the chars of an [`ImString`].
+pub struct IntoChars<S: Data<String>> {
+    string: ImString<S>,
+}
+
+impl<S: Data<String>> IntoChars<S> {
+    /// Returns the remaining chars of this iterator as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.string.as_str()
+    }
+}
+
+impl<S: Data<String>> Iterator for IntoChars<S> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.string.chars().next()?;
+        self.string.offset.start += c.len_utf8();
+        Some(c)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.string.chars().size_hint()
+    }
+}
+
+impl<S: Data<String>> DoubleEndedIterator for IntoChars<S> {
+    fn next_back(&mut self) -> Option<char> {
+        let c = self.string.chars().next_back()?;
+        self.string.offset.end -= c.len_utf8();
+        Some(c)
+    }
+}
+
+impl<S: Data<String>> FusedIterator for IntoChars<S> {}
+
+/// Owning iterator over the chars of an [`ImString`] and their byte positions.
+///
+/// Returned by [`ImString::char_indices_owned`]. It is also used as the `InputIter` iterator for
+/// `nom` parsers when the `nom` feature is enabled.
+pub struct IntoCharIndices<S: Data<String>> {
+    start: usize,
+    chars: IntoChars<S>,
+}
+
+impl<S: Data<String>> IntoCharIndices<S> {
+    /// Returns the remaining chars of this iterator as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.chars.as_str()
+    }
+}
+
+impl<S: Data<String>> Iterator for IntoCharIndices<S> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.chars.string.offset.start - self.start;
+        self.chars.next().map(|c| (index, c))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chars.size_hint()
+    }
+}
+
+impl<S: Data<String>> DoubleEndedIterator for IntoCharIndices<S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let c = self.chars.next_back()?;
+        Some((self.chars.string.offset.end - self.start, c))
+    }
+}
+
+impl<S: Data<String>> FusedIterator for IntoCharIndices<S> {}
+
+/// Owning iterator over the bytes of an [`ImString`].
+///
+/// Returned by [`ImString::bytes_owned`].
+pub struct IntoBytes<S: Data<String>> {
+    // the iterator can stop in the middle of a char, so this cannot be an ImString
+    string: S,
+    offset: Range<usize>,
+}
+
+impl<S: Data<String>> IntoBytes<S> {
+    /// Returns the remaining bytes of this iterator as a slice.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.string.get_borrowed().as_bytes()[self.offset.clone()]
+    }
+}
+
+impl<S: Data<String>> Iterator for IntoBytes<S> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = *self.as_bytes().first()?;
+        self.offset.start += 1;
+        Some(byte)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.offset.len(), Some(self.offset.len()))
+    }
+}
+
+impl<S: Data<String>> DoubleEndedIterator for IntoBytes<S> {
+    fn next_back(&mut self) -> Option<u8> {
+        let byte = *self.as_bytes().last()?;
+        self.offset.end -= 1;
+        Some(byte)
+    }
+}
+
+impl<S: Data<String>> ExactSizeIterator for IntoBytes<S> {
+    fn len(&self) -> usize {
+        self.offset.len()
+    }
+}
+
+impl<S: Data<String>> FusedIterator for IntoBytes<S> {}
+
+impl<S: Data<String>> IntoIterator for ImString<S> {
+    type Item = char;
+    type IntoIter = IntoChars<S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoChars { string: self }
+    }
+}
+
+impl<'a, S: Data<String>> IntoIterator for &'a ImString<S> {
+    type Item = char;
+    type IntoIter = std::str::Chars<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chars()
+    }
+}
+
+/// Iterator returned by [`ImString::char_slices`].
+pub struct CharSlices<'a, S: Data<String>> {
+    string: &'a S,
+    slice: &'a str,
+    iterator: std::str::CharIndices<'a>,
+}
+
+impl<'a, S: Data<String>> CharSlices<'a, S> {
+    fn wrap(&self, (index, c): (usize, char)) -> (usize, ImString<S>) {
+        let slice = &self.slice[index..index + c.len_utf8()];
+        let offset =
+            try_slice_offset(self.string.get_borrowed().as_bytes(), slice.as_bytes()).unwrap();
+        let string = ImString {
+            string: self.string.clone(),
+            offset,
+        };
+        (index, string)
+    }
+}
+
+impl<'a, S: Data<String>> Iterator for CharSlices<'a, S> {
+    type Item = (usize, ImString<S>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|item| self.wrap(item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a, S: Data<String>> DoubleEndedIterator for CharSlices<'a, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iterator.next_back().map(|item| self.wrap(item))
+    }
+}
+
+impl<'a, S: Data<String>> FusedIterator for CharSlices<'a, S> {}
+
+/// Iterator returned by [`ImString::chunks_bytes`].
+///
+/// The chunks are counted once when the iterator is created, so its
+/// [`len()`](ExactSizeIterator::len) is cheap.
+pub struct ChunksBytes<S: Data<String>> {
+    string: ImString<S>,
+    size: usize,
+    /// Number of chunks which have not been returned yet.
+    remaining: usize,
+}
+
+impl<S: Data<String>> ChunksBytes<S> {
+    /// Returns the remaining part of the string which has not been chunked yet.
+    pub fn as_str(&self) -> &str {
+        self.string.as_str()
+    }
+
+    /// Returns the length of the first chunk of `string`, which is zero if it is empty.
+    fn chunk_len(&self, string: &str) -> usize {
+        let mut end = self.size.min(string.len());
+        while !string.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            end = string.chars().next().map_or(0, char::len_utf8);
+        }
+        end
+    }
+}
+
+impl<S: Data<String>> Iterator for ChunksBytes<S> {
+    type Item = ImString<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.chunk_len(self.string.as_str());
+        if end == 0 {
+            return None;
+        }
+        let chunk = unsafe { self.string.slice_unchecked(..end) };
+        self.string.offset.start += end;
+        self.remaining -= 1;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<S: Data<String>> ExactSizeIterator for ChunksBytes<S> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<S: Data<String>> FusedIterator for ChunksBytes<S> {}
+
+/// Iterator returned by [`ImString::chunks_chars`].
+///
+/// The chars are counted once when the iterator is created, so its
+/// [`len()`](ExactSizeIterator::len) is cheap.
+pub struct ChunksChars<S: Data<String>> {
+    string: ImString<S>,
+    size: usize,
+    /// Number of chars which have not been returned yet.
+    remaining: usize,
+}
+
+impl<S: Data<String>> ChunksChars<S> {
+    /// Returns the remaining part of the string which has not been chunked yet.
+    pub fn as_str(&self) -> &str {
+        self.string.as_str()
+    }
+}
+
+impl<S: Data<String>> Iterator for ChunksChars<S> {
+    type Item = ImString<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let string = self.string.as_str();
+        if string.is_empty() {
+            return None;
+        }
+        let end = string
+            .char_indices()
+            .nth(self.size)
+            .map_or(string.len(), |(index, _)| index);
+        let chunk = unsafe { self.string.slice_unchecked(..end) };
+        self.string.offset.start += end;
+        self.remaining -= self.size.min(self.remaining);
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl<S: Data<String>> ExactSizeIterator for ChunksChars<S> {
+    fn len(&self) -> usize {
+        self.remaining / self.size + usize::from(self.remaining % self.size != 0)
+    }
+}
+
+impl<S: Data<String>> FusedIterator for ChunksChars<S> {}
+
+/// Iterator returned by [`ImString::match_indices`].
+pub struct MatchIndices<'a, S: Data<String>> {
+    string: &'a S,
+    iterator: std::str::MatchIndices<'a, &'a str>,
+}
+
+impl<'a, S: Data<String>> MatchIndices<'a, S> {
+    fn wrap(&self, (index, slice): (usize, &str)) -> (usize, ImString<S>) {
+        let offset =
+            try_slice_offset(self.string.get_borrowed().as_bytes(), slice.as_bytes()).unwrap();
+        let string = ImString {
+            string: self.string.clone(),
+            offset,
+        };
+        (index, string)
+    }
+}
+
+impl<'a, S: Data<String>> Iterator for MatchIndices<'a, S> {
+    type Item = (usize, ImString<S>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next().map(|item| self.wrap(item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a, S: Data<String>> FusedIterator for MatchIndices<'a, S> {}
+
+/// Wrapper which compares and hashes strings by their bytes.
+///
+/// Unlike [`ImString`], which hashes like a `str`, a `ByteKey` hashes like a `[u8]` and implements
+/// [`Borrow<[u8]>`](Borrow). This makes it suitable as a key in a
+/// [`HashMap`](std::collections::HashMap) or [`BTreeMap`](std::collections::BTreeMap) which is
+/// looked up using byte slices.
+///
+/// `ImString` does not implement `Borrow<[u8]>` itself, because [`Borrow`] requires borrowed
+/// values to hash the same way as the original, and a `str` hashes differently from its bytes.
+/// A `HashMap` keyed by `ImString` would therefore never find an entry looked up by bytes.
+///
+/// # Example
+///
+/// ```rust
+/// # use imstr::ImString;
+/// use imstr::string::ByteKey;
+/// use std::collections::HashMap;
+///
+/// let mut map = HashMap::new();
+/// map.insert(ByteKey(ImString::from("key")), "value");
+/// assert_eq!(map.get(b"key".as_slice()), Some(&"value"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ByteKey<T>(pub T);
+
+impl<T: AsRef<[u8]>> PartialEq for ByteKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl<T: AsRef<[u8]>> Eq for ByteKey<T> {}
+
+impl<T: AsRef<[u8]>> PartialOrd for ByteKey<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: AsRef<[u8]>> Ord for ByteKey<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.as_ref().cmp(other.0.as_ref())
+    }
+}
+
+impl<T: AsRef<[u8]>> Hash for ByteKey<T> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.0.as_ref().hash(hasher)
+    }
+}
+
+impl<T: AsRef<[u8]>> Borrow<[u8]> for ByteKey<T> {
+    fn borrow(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+/// Reader returned by [`ImString::reader`].
+pub struct ImStringReader<S: Data<String>> {
+    // the reader can stop in the middle of a char, so this cannot be an ImString
+    string: S,
+    offset: Range<usize>,
+}
+
+impl<S: Data<String>> ImStringReader<S> {
+    fn remaining(&self) -> &[u8] {
+        &self.string.get_borrowed().as_bytes()[self.offset.clone()]
+    }
+}
+
+impl<S: Data<String>> Read for ImStringReader<S> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let length = buffer.len().min(self.offset.len());
+        buffer[..length].copy_from_slice(&self.remaining()[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<S: Data<String>> BufRead for ImStringReader<S> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        Ok(self.remaining())
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.offset.start += amount.min(self.offset.len());
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::InputLength for ImString<S> {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::InputTake for ImString<S> {
+    fn take(&self, count: usize) -> Self {
+        self.slice(..count)
+    }
+
+    fn take_split(&self, count: usize) -> (Self, Self) {
+        let (prefix, suffix) = self.split_at(count);
+        (suffix, prefix)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::InputIter for ImString<S> {
+    type Item = char;
+    type Iter = IntoCharIndices<S>;
+    type IterElem = IntoChars<S>;
+
+    fn iter_indices(&self) -> Self::Iter {
+        self.char_indices_owned()
+    }
+
+    fn iter_elements(&self) -> Self::IterElem {
+        self.clone().into_iter()
+    }
+
+    fn position<P: Fn(Self::Item) -> bool>(&self, predicate: P) -> Option<usize> {
+        self.as_str().find(predicate)
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
+        self.as_str()
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(self.len()))
+            .nth(count)
+            .ok_or(nom::Needed::Unknown)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::InputTakeAtPosition for ImString<S> {
+    type Item = char;
+
+    fn split_at_position<P, E>(&self, predicate: P) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.as_str().find(predicate) {
+            Some(index) => Ok(nom::InputTake::take_split(self, index)),
+            None => Err(nom::Err::Incomplete(nom::Needed::new(1))),
+        }
+    }
+
+    fn split_at_position1<P, E>(
+        &self,
+        predicate: P,
+        kind: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.as_str().find(predicate) {
+            Some(0) => Err(nom::Err::Error(E::from_error_kind(self.clone(), kind))),
+            Some(index) => Ok(nom::InputTake::take_split(self, index)),
+            None => Err(nom::Err::Incomplete(nom::Needed::new(1))),
+        }
+    }
+
+    fn split_at_position_complete<P, E>(&self, predicate: P) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        let index = self.as_str().find(predicate).unwrap_or(self.len());
+        Ok(nom::InputTake::take_split(self, index))
+    }
+
+    fn split_at_position1_complete<P, E>(
+        &self,
+        predicate: P,
+        kind: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.as_str().find(predicate).unwrap_or(self.len()) {
+            0 => Err(nom::Err::Error(E::from_error_kind(self.clone(), kind))),
+            index => Ok(nom::InputTake::take_split(self, index)),
+        }
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<'a, S: Data<String>> nom::Compare<&'a str> for ImString<S> {
+    fn compare(&self, other: &'a str) -> nom::CompareResult {
+        nom::Compare::compare(&self.as_str(), other)
+    }
+
+    fn compare_no_case(&self, other: &'a str) -> nom::CompareResult {
+        nom::Compare::compare_no_case(&self.as_str(), other)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<'a, S: Data<String>> nom::FindSubstring<&'a str> for ImString<S> {
+    fn find_substring(&self, substring: &'a str) -> Option<usize> {
+        self.as_str().find(substring)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::Offset for ImString<S> {
+    fn offset(&self, second: &Self) -> usize {
+        second.offset.start - self.offset.start
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::Slice<Range<usize>> for ImString<S> {
+    fn slice(&self, range: Range<usize>) -> Self {
+        ImString::slice(self, range)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::Slice<RangeFrom<usize>> for ImString<S> {
+    fn slice(&self, range: RangeFrom<usize>) -> Self {
+        ImString::slice(self, range)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::Slice<RangeTo<usize>> for ImString<S> {
+    fn slice(&self, range: RangeTo<usize>) -> Self {
+        ImString::slice(self, range)
+    }
+}
+
+#[cfg(feature = "nom")]
+impl<S: Data<String>> nom::Slice<RangeFull> for ImString<S> {
+    fn slice(&self, range: RangeFull) -> Self {
+        ImString::slice(self, range)
+    }
+}
+
+/// Wrapper which compares and hashes strings ignoring their ASCII case.
+///
+/// Two `CaseInsensitive` values are equal if their contents are equal when ignoring ASCII case,
+/// see [`ImString::eq_ignore_ascii_case`]. The hash is computed over the ASCII lowercased bytes,
+/// so that values which compare equal also have equal hashes. This makes it suitable as a key in a
+/// [`HashMap`](std::collections::HashMap) or [`HashSet`](std::collections::HashSet).
+///
+/// # Example
+///
+/// ```rust
+/// # use imstr::ImString;
+/// use imstr::string::CaseInsensitive;
+/// use std::collections::HashMap;
+///
+/// let mut headers = HashMap::new();
+/// headers.insert(CaseInsensitive(ImString::from("Content-Type")), "text/plain");
+/// let key = CaseInsensitive(ImString::from("content-type"));
+/// assert_eq!(headers.get(&key), Some(&"text/plain"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaseInsensitive<T>(pub T);
+
+impl<T: AsRef<str>> PartialEq for CaseInsensitive<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_ref().eq_ignore_ascii_case(other.0.as_ref())
+    }
+}
+
+impl<T: AsRef<str>> Eq for CaseInsensitive<T> {}
+
+impl<T: AsRef<str>> Hash for CaseInsensitive<T> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        for byte in self.0.as_ref().bytes() {
+            hasher.write_u8(byte.to_ascii_lowercase());
+        }
+        // same terminator as the Hash implementation of str, to make prefixes hash differently
+        hasher.write_u8(0xff);
+    }
+}
+
+#[cfg(test)]
+fn test_strings<S: Data<String>>() -> Vec<ImString<S>> {
+    let long = ImString::from("long string here");
+    let world = ImString::from("world");
+    let some = ImString::from("some");
+    let multiline = ImString::from("some\nmulti\nline\nstring\nthat\nis\nlong");
+    vec![
+        ImString::new(),
+        ImString::default(),
+        ImString::from("hello"),
+        ImString::from("0.0.0.0:800"),
+        ImString::from("localhost:1234"),
+        long.clone(),
+        long.slice(4..10),
+        long.slice(0..4),
+        long.slice(4..4),
+        long.slice(5..),
+        long.slice(..),
+        world.clone(),
+        world.clone(),
+        some.slice(4..),
+        some,
+        multiline.slice(5..15),
+        multiline,
+        ImString::from("\u{e4}\u{fc}\u{f6}\u{f8}\u{3a9}"),
+        ImString::from("\u{1f600}\u{1f603}\u{1f604}"),
+        ImString::from("o\u{308}u\u{308}a\u{308}"),
+    ]
+}
+
+macro_rules! tests {
+    () => {};
+    (#[test] fn $name:ident <S: Data<String>>() $body:tt $($rest:tt)*) => {
+        #[test]
+        fn $name() {
+            fn $name <S: Data<String>>() $body
+            $name::<Threadsafe>();
+            $name::<Local>();
+            $name::<Cloned<String>>();
+            $name::<Box<String>>();
+            $name::<Static>();
+            $name::<Inline>();
+        }
+        tests!{$($rest)*}
+    };
+    (#[test] fn $name:ident <S: Data<String>>($string:ident: ImString<S>) $body:tt $($rest:tt)*) => {
+        #[test]
+        fn $name() {
+            fn $name <S: Data<String>>() {
+                fn $name <S: Data<String>>($string: ImString<S>) $body
+                for string in test_strings::<S>().into_iter() {
+                    $name(string);
+                }
+            }
+            $name::<Threadsafe>();
+            $name::<Local>();
+            $name::<Cloned<String>>();
+            $name::<Box<String>>();
+            $name::<Static>();
+            $name::<Inline>();
+        }
+        tests!{$($rest)*}
+    }
+}
+
+tests! {
+    #[test]
+    fn test_new<S: Data<String>>() {
+        let string: ImString<S> = ImString::new();
+        assert_eq!(string.string.get_borrowed().len(), 0);
+        assert_eq!(string.offset, 0..0);
+    }
+
+    #[test]
+    fn test_default<S: Data<String>>() {
+        let string: ImString<S> = ImString::new();
+        assert_eq!(string.string.get_borrowed().len(), 0);
+        assert_eq!(string.offset, 0..0);
+    }
+
+    #[test]
+    fn test_with_capacity<S: Data<String>>() {
+        for capacity in [10, 100, 256] {
+            let string: ImString<S> = ImString::with_capacity(capacity);
+            assert!(string.capacity() >= capacity);
+            assert_eq!(string.string.get_borrowed().len(), 0);
+            assert_eq!(string.offset, 0..0);
+        }
+    }
+
+    #[test]
+    fn test_offset<S: Data<String>>(string: ImString<S>) {
+        assert!(string.offset.start <= string.string.get_borrowed().len());
+        assert!(string.offset.start <= string.offset.end);
+        assert!(string.offset.end <= string.string.get_borrowed().len());
+    }
+
+    #[test]
+    fn test_as_str<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.as_str(), &string.string.get_borrowed()[string.offset.clone()]);
+        assert_eq!(string.as_str().len(), string.len());
+    }
+
+    #[test]
+    fn test_as_bytes<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.as_bytes(), &string.string.get_borrowed().as_bytes()[string.offset.clone()]);
+        assert_eq!(string.as_bytes().len(), string.len());
+    }
+
+    #[test]
+    fn test_len<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.len(), string.offset.len());
+        assert_eq!(string.len(), string.as_str().len());
+        assert_eq!(string.len(), string.as_bytes().len());
+    }
+
+    #[test]
+    fn test_clear<S: Data<String>>(string: ImString<S>) {
+        let mut string = string;
+        string.clear();
+        assert_eq!(string.as_str(), "");
+        assert_eq!(string.len(), 0);
+    }
+
+    #[test]
+    fn test_debug<S: Data<String>>(string: ImString<S>) {
+        let debug_string = format!("{string:?}");
+        let debug_str = format!("{:?}", string.as_str());
+        assert_eq!(debug_string, debug_str);
+    }
+
+    #[test]
+    fn test_deref<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.deref(), string.as_str());
+    }
+
+    #[test]
+    fn test_clone<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string, string.clone());
+    }
+
+    #[test]
+    fn test_display<S: Data<String>>(string: ImString<S>) {
+        let display_string = format!("{string}");
+        let display_str = string.as_str().to_string();
+        assert_eq!(display_string, display_str);
+    }
+
+    #[test]
+    fn test_insert_start<S: Data<String>>(string: ImString<S>) {
+        let mut string = string;
+        let length = string.len();
+        string.insert(0, 'h');
+        assert_eq!(string.len(), length + 1);
+        assert_eq!(string.chars().next(), Some('h'));
+    }
+
+    #[test]
+    fn test_insert_one<S: Data<String>>(string: ImString<S>) {
+        if !string.is_empty() && string.is_char_boundary(1) {
+            let mut string = string;
+            let length = string.len();
+            string.insert(1, 'h');
+            assert_eq!(string.len(), length + 1);
+            assert_eq!(string.chars().nth(1), Some('h'));
+        }
+    }
+
+    #[test]
+    fn test_insert_end<S: Data<String>>(string: ImString<S>) {
+        let mut string = string;
+        let length = string.len();
+        string.insert(length, 'h');
+        assert_eq!(string.len(), length + 1);
+        // FIXME
+        //assert_eq!(string.chars().nth(length), Some('h'));
+    }
+
+    #[test]
+    fn test_is_empty<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.is_empty(), string.as_str().is_empty());
+    }
+
+    #[test]
+    fn test_push<S: Data<String>>(string: ImString<S>) {
+        let mut string = string;
+        let mut std_string = string.as_str().to_string();
+        let c = 'c';
+        std_string.push(c);
+        string.push(c);
+        assert_eq!(string, std_string);
+    }
+
+    #[test]
+    fn test_push_str<S: Data<String>>(string: ImString<S>) {
+        let mut string = string;
+        let mut std_string = string.as_str().to_string();
+        let s = "string";
+        std_string.push_str(s);
+        string.push_str(s);
+        assert_eq!(string, std_string);
+    }
+
+    #[test]
+    fn test_slice_all<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.slice(..), string);
+    }
+
+    #[test]
+    fn test_slice_start<S: Data<String>>(string: ImString<S>) {
+        for end in 0..string.len() {
+            if string.is_char_boundary(end) {
+                assert_eq!(string.slice(..end), string.as_str()[..end]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice_end<S: Data<String>>(string: ImString<S>) {
+        for start in 0..string.len() {
+            if string.is_char_boundary(start) {
+                assert_eq!(string.slice(start..), string.as_str()[start..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice_middle<S: Data<String>>(string: ImString<S>) {
+        for start in 0..string.len() {
+            if string.is_char_boundary(start) {
+                for end in start..string.len() {
+                    if string.is_char_boundary(end) {
+                        assert_eq!(string.slice(start..end), string.as_str()[start..end]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_slice_all<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.try_slice(..).unwrap(), string);
+    }
+
+    #[test]
+    fn test_try_slice_start<S: Data<String>>(string: ImString<S>) {
+        for end in 0..string.len() {
+            if string.is_char_boundary(end) {
+                assert_eq!(string.try_slice(..end).unwrap(), string.as_str()[..end]);
+            } else {
+                // cannot get slice with end in middle of UTF-8 multibyte sequence.
+                assert_eq!(string.try_slice(..end), Err(SliceError::EndNotAligned));
+            }
+        }
+
+        // cannot get slice with end pointing past the end of the string.
+        assert_eq!(string.try_slice(..string.len()+1), Err(SliceError::EndOutOfBounds));
+    }
+
+    #[test]
+    fn test_try_slice_end<S: Data<String>>(string: ImString<S>) {
+        for start in 0..string.len() {
+            if string.is_char_boundary(start) {
+                assert_eq!(string.try_slice(start..).unwrap(), string.as_str()[start..]);
+            } else {
+                // cannot get slice with end in middle of UTF-8 multibyte sequence.
+                assert_eq!(string.try_slice(start..), Err(SliceError::StartNotAligned));
+            }
+        }
+
+        // cannot get slice with end pointing past the end of the string.
+        assert_eq!(string.try_slice(string.len()+1..), Err(SliceError::StartOutOfBounds));
+    }
+
+    #[test]
+    fn test_add_assign<S: Data<String>>(string: ImString<S>) {
+        let mut std_string = string.as_str().to_string();
+        let mut string = string;
+        string += "hello";
+        std_string += "hello";
+        assert_eq!(string, std_string);
+    }
+
+    #[test]
+    fn test_add<S: Data<String>>(string: ImString<S>) {
+        let std_string = string.as_str().to_string();
+        let std_string = std_string + "hello";
+        let string = string + "hello";
+        assert_eq!(string, std_string);
+    }
+
+    #[test]
+    fn test_to_socket_addrs<S: Data<String>>(string: ImString<S>) {
+        #[cfg(not(miri))]
+        {
+            let addrs = string.to_socket_addrs().map(|s| s.collect::<Vec<_>>());
+            let str_addrs = string.as_str().to_socket_addrs().map(|s| s.collect::<Vec<_>>());
+            match addrs {
+                Ok(addrs) => assert_eq!(addrs, str_addrs.unwrap()),
+                Err(_) => assert!(str_addrs.is_err()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_iterator_char<S: Data<String>>() {
+        let input = ['h', 'e', 'l', 'l', 'o'];
+        let string: ImString<S> = input.into_iter().collect();
+        assert_eq!(string, "hello");
+    }
+
+    #[test]
+    fn test_from_iterator_char_ref<S: Data<String>>() {
+        let input = ['h', 'e', 'l', 'l', 'o'];
+        let string: ImString<S> = input.iter().collect();
+        assert_eq!(string, "hello");
+    }
+
+    #[test]
+    fn test_from_iterator_str<S: Data<String>>() {
+        let input = ["hello", "world", "!"];
+        let string: ImString<S> = input.into_iter().collect();
+        assert_eq!(string, "helloworld!");
+    }
+
+    #[test]
+    fn test_extend_char<S: Data<String>>() {
+        let input = ['h', 'e', 'l', 'l', 'o'];
+        let mut string: ImString<S> = ImString::new();
+        string.extend(input.into_iter());
+        assert_eq!(string, "hello");
+    }
+
+    #[test]
+    fn test_extend_char_ref<S: Data<String>>() {
+        let input = ['h', 'e', 'l', 'l', 'o'];
+        let mut string: ImString<S> = ImString::new();
+        string.extend(input.into_iter());
+        assert_eq!(string, "hello");
+    }
+
+    #[test]
+    fn test_extend_str<S: Data<String>>() {
+        let input = ["hello", "world", "!"];
+        let mut string: ImString<S> = ImString::new();
+        string.extend(input.into_iter());
+        assert_eq!(string, "helloworld!");
+    }
+
+    #[test]
+    fn test_from_utf8_lossy<S: Data<String>>() {
+        let string: ImString<S> = ImString::from_utf8_lossy(b"hello");
+        assert_eq!(string, "hello");
+    }
+
+    #[test]
+    fn test_from_utf8_unchecked<S: Data<String>>() {
+        let string: ImString<S> = unsafe {
+            ImString::from_utf8_unchecked(b"hello".to_vec())
+        };
+        assert_eq!(string, "hello");
+    }
+
+    #[test]
+    fn test_as_ref_str<S: Data<String>>(string: ImString<S>) {
+        let s: &str = string.as_ref();
+        assert_eq!(s, string.as_str());
+    }
+
+    #[test]
+    fn test_as_ref_bytes<S: Data<String>>(string: ImString<S>) {
+        let s: &[u8] = string.as_ref();
+        assert_eq!(s, string.as_bytes());
+    }
+
+    #[test]
+    fn test_as_ref_path<S: Data<String>>(string: ImString<S>) {
+        let s: &Path = string.as_ref();
+        assert_eq!(s, string.as_str().as_ref() as &Path);
+    }
+
+    #[test]
+    fn test_as_ref_os_str<S: Data<String>>(string: ImString<S>) {
+        let s: &OsStr = string.as_ref();
+        assert_eq!(s, string.as_str().as_ref() as &OsStr);
+    }
+
+    #[test]
+    fn test_partial_eq<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string, string.as_str());
+        assert_eq!(string, string.to_string());
+        assert_eq!(string, string);
+    }
+
+    #[test]
+    fn test_from<S: Data<String>>(string: ImString<S>) {
+        let std_string: String = string.clone().into();
+        assert_eq!(string, std_string);
+    }
+
+    #[test]
+    fn test_raw_offset<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.offset, string.raw_offset());
+    }
+
+    #[test]
+    fn test_raw_string<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.string.get_borrowed(), string.raw_string().get_borrowed());
+    }
+
+    #[test]
+    fn into_std_string<S: Data<String>>(string: ImString<S>) {
+        let std_clone = string.as_str().to_string();
+        let std_string = string.into_std_string();
+        assert_eq!(std_clone, std_string);
+    }
+
+    #[test]
+    fn test_truncate<S: Data<String>>(string: ImString<S>) {
+        let mut clone = string.as_str().to_string();
+        let mut string = string;
+
+        for length in (0..string.len()).rev() {
+            if string.is_char_boundary(length) {
+                string.truncate(length);
+                clone.truncate(length);
+                assert_eq!(string, clone);
+            }
+        }
+    }
+
+    #[test]
+    fn test_str_ref<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string, string.str_ref(string.as_str()));
+    }
+
+    #[test]
+    fn test_try_str_ref<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string, string.try_str_ref(string.as_str()).unwrap());
+        assert_eq!(string.try_str_ref("test"), None);
+    }
+
+    #[test]
+    fn test_slice_ref<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string, string.slice_ref(string.as_bytes()));
+    }
+
+    #[test]
+    fn test_try_slice_ref<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string, string.try_slice_ref(string.as_bytes()).unwrap());
+        assert_eq!(string.try_slice_ref(b"test"), None);
+    }
+
+    #[test]
+    fn test_from_parts<S: Data<String>>(string: ImString<S>) {
+        let parts = ImString::from_parts(string.raw_string(), string.raw_offset()).unwrap();
+        assert_eq!(parts, string);
+        assert_eq!(parts.raw_offset(), string.raw_offset());
+
+        let parts = unsafe {
+            ImString::from_parts_unchecked(string.raw_string(), string.raw_offset())
+        };
+        assert_eq!(parts, string);
+
+        let length = string.raw_string().get_borrowed().len();
+        assert_eq!(
+            ImString::from_parts(string.raw_string(), length + 1..length + 1),
+            Err(SliceError::StartOutOfBounds)
+        );
+        assert_eq!(
+            ImString::from_parts(string.raw_string(), 0..length + 1),
+            Err(SliceError::EndOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_from_parts_invalid<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{1f600}");
+        assert_eq!(
+            ImString::from_parts(string.raw_string(), 1..4),
+            Err(SliceError::StartNotAligned)
+        );
+        assert_eq!(
+            ImString::from_parts(string.raw_string(), 0..2),
+            Err(SliceError::EndNotAligned)
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let range = 4..0;
+        assert_eq!(
+            ImString::from_parts(string.raw_string(), range),
+            Err(SliceError::EndBeforeStart)
+        );
+    }
+
+    #[test]
+    fn test_capitalize_first<S: Data<String>>(string: ImString<S>) {
+        let capitalized = string.capitalize_first();
+        let mut chars = string.chars();
+        let expected: String = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+        assert_eq!(capitalized, expected);
+    }
+
+    #[test]
+    fn test_capitalize_first_expands<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{df}tra\u{df}e");
+        assert_eq!(string.capitalize_first(), "SStra\u{df}e");
+    }
+
+    #[test]
+    fn test_lines<S: Data<String>>(string: ImString<S>) {
+        let lines: Vec<_> = string.lines().collect();
+        let std_lines: Vec<_> = string.as_str().lines().collect();
+        assert_eq!(lines, std_lines);
+    }
+
+    #[test]
+    fn test_lines_next_back<S: Data<String>>(string: ImString<S>) {
+        let mut lines = string.lines();
+        let mut std_lines = string.as_str().lines();
+        assert_eq!(lines.next_back(), std_lines.next_back().map(ImString::<S>::from));
+        assert_eq!(lines.next(), std_lines.next().map(ImString::<S>::from));
+        assert_eq!(lines.next_back(), std_lines.next_back().map(ImString::<S>::from));
+    }
+
+    #[test]
+    fn test_lines_size_hint<S: Data<String>>(string: ImString<S>) {
+        let mut lines = string.lines();
+        let mut std_lines = string.as_str().lines();
+        assert_eq!(lines.size_hint(), std_lines.size_hint());
+        while lines.next().is_some() {
+            std_lines.next();
+            assert_eq!(lines.size_hint(), std_lines.size_hint());
+        }
+    }
+
+    #[test]
+    fn test_lines_fused<S: Data<String>>(string: ImString<S>) {
+        let mut lines = string.lines();
+        for _ in lines.by_ref() {}
+        for _ in 0..3 {
+            assert_eq!(lines.next(), None);
+        }
+    }
+
+    #[test]
+    fn test_chunks_exact_size<S: Data<String>>(string: ImString<S>) {
+        for size in 1..8 {
+            assert_eq!(string.chunks_bytes(size).len(), string.chunks_bytes(size).count());
+            assert_eq!(string.chunks_chars(size).len(), string.chunks_chars(size).count());
+            let mut chunks = string.chunks_bytes(size);
+            if chunks.next().is_some() {
+                assert_eq!(chunks.len(), chunks.count());
+            }
+            let mut chunks = string.chunks_chars(size);
+            if chunks.next().is_some() {
+                assert_eq!(chunks.len(), chunks.count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_clone_as<S: Data<String>>(string: ImString<S>) {
+        let threadsafe: ImString<Threadsafe> = string.clone_as();
+        assert_eq!(threadsafe, string);
+        assert_eq!(threadsafe.offset, 0..string.len());
+        let local: ImString<Local> = string.clone_as();
+        assert_eq!(local, string);
+        assert_eq!(local.offset, 0..string.len());
+        let cloned: ImString<Cloned<String>> = string.clone_as();
+        assert_eq!(cloned, string);
+        assert_eq!(cloned.offset, 0..string.len());
+        let boxed: ImString<Box<String>> = string.clone_as();
+        assert_eq!(boxed, string);
+        assert_eq!(boxed.offset, 0..string.len());
+    }
+
+    #[test]
+    fn test_prefix<S: Data<String>>(string: ImString<S>) {
+        let count = string.chars().count();
+        for n in 0..count + 2 {
+            let expected: String = string.chars().take(n).collect();
+            assert_eq!(string.prefix(n), expected);
+        }
+        assert_eq!(string.prefix(count + 1).offset, string.offset);
+    }
+
+    #[test]
+    fn test_suffix<S: Data<String>>(string: ImString<S>) {
+        let count = string.chars().count();
+        for n in 0..count + 2 {
+            let expected: String = string.chars().skip(count.saturating_sub(n)).collect();
+            assert_eq!(string.suffix(n), expected);
+        }
+        assert_eq!(string.suffix(count + 1).offset, string.offset);
+    }
+
+    #[test]
+    fn test_translate<S: Data<String>>(string: ImString<S>) {
+        let translated = string.translate("lo\u{e4}", "LO\u{1f600}");
+        let expected: String = string
+            .chars()
+            .map(|c| match c {
+                'l' => 'L',
+                'o' => 'O',
+                '\u{e4}' => '\u{1f600}',
+                c => c,
+            })
+            .collect();
+        assert_eq!(translated, expected);
+        assert_eq!(string.translate("", ""), string);
+    }
+
+    #[test]
+    fn test_translate_abc<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("cab");
+        assert_eq!(string.translate("abc", "xyz"), "zxy");
+    }
+
+    #[test]
+    fn test_trim<S: Data<String>>(string: ImString<S>) {
+        for string in [string.clone(), ImString::from(format!(" \t{string}\n "))] {
+            let trimmed = string.trim();
+            assert_eq!(trimmed, string.as_str().trim());
+            assert!(trimmed.offset.start >= string.offset.start);
+            assert!(trimmed.offset.end <= string.offset.end);
+        }
+    }
+
+    #[test]
+    fn test_trim_start<S: Data<String>>(string: ImString<S>) {
+        for string in [string.clone(), ImString::from(format!(" \t{string}\n "))] {
+            let trimmed = string.trim_start();
+            assert_eq!(trimmed, string.as_str().trim_start());
+            assert_eq!(trimmed.offset.end, string.offset.end);
+        }
+    }
+
+    #[test]
+    fn test_trim_end<S: Data<String>>(string: ImString<S>) {
+        for string in [string.clone(), ImString::from(format!(" \t{string}\n "))] {
+            let trimmed = string.trim_end();
+            assert_eq!(trimmed, string.as_str().trim_end());
+            assert_eq!(trimmed.offset.start, string.offset.start);
+        }
+    }
+
+    #[test]
+    fn test_trim_whitespace<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("long \t\n string");
+        let whitespace = string.slice(4..8);
+        for trimmed in [whitespace.trim(), whitespace.trim_start(), whitespace.trim_end()] {
+            assert_eq!(trimmed, "");
+            assert!(trimmed.offset.is_empty());
+            assert!(trimmed.offset.start >= 4 && trimmed.offset.end <= 8);
+        }
+    }
+
+    #[test]
+    fn test_heap_size<S: Data<String>>(string: ImString<S>) {
+        // inline data has capacity without using the heap
+        let inline = string.string.inline_capacity();
+        assert_eq!(string.heap_size(), string.capacity() - inline);
+        assert!(string.shared_heap_size() <= string.heap_size());
+    }
+
+    #[test]
+    fn test_heap_size_unique<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello world");
+        let inline = string.string.inline_capacity();
+        assert_eq!(string.heap_size(), string.capacity() - inline);
+        assert_eq!(string.shared_heap_size(), string.capacity() - inline);
+    }
+
+    #[test]
+    fn test_strip_prefix<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.strip_prefix(""), Some(string.clone()));
+        assert_eq!(string.strip_prefix(string.as_str()).unwrap(), "");
+        for end in 0..=string.len() {
+            if let Some(prefix) = string.as_str().get(..end) {
+                let stripped = string.strip_prefix(prefix).unwrap();
+                assert_eq!(stripped, string.as_str()[end..]);
+                assert_eq!(stripped.offset, string.offset.start + end..string.offset.end);
+            }
+        }
+        let prefix = format!("{string}!");
+        assert_eq!(string.strip_prefix(&prefix), None);
+    }
+
+    #[test]
+    fn test_strip_suffix<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.strip_suffix(""), Some(string.clone()));
+        assert_eq!(string.strip_suffix(string.as_str()).unwrap(), "");
+        for start in 0..=string.len() {
+            if let Some(suffix) = string.as_str().get(start..) {
+                let stripped = string.strip_suffix(suffix).unwrap();
+                assert_eq!(stripped, string.as_str()[..start]);
+                assert_eq!(stripped.offset, string.offset.start..string.offset.start + start);
+            }
+        }
+        let suffix = format!("!{string}");
+        assert_eq!(string.strip_suffix(&suffix), None);
+    }
+
+    #[test]
+    fn test_strip_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{1f600}\u{e4}b\u{1f603}");
+        assert_eq!(string.strip_prefix("\u{1f600}").unwrap(), "\u{e4}b\u{1f603}");
+        assert_eq!(string.strip_suffix("b\u{1f603}").unwrap(), "\u{1f600}\u{e4}");
+        assert_eq!(string.strip_prefix("\u{e4}"), None);
+        assert_eq!(string.strip_suffix("\u{1f600}"), None);
+    }
+
+    #[test]
+    fn test_split<S: Data<String>>(string: ImString<S>) {
+        for pattern in ["", " ", "\n", "o", "lo", "\u{fc}"] {
+            let parts: Vec<_> = string.split(pattern).collect();
+            let std_parts: Vec<_> = string.as_str().split(pattern).collect();
+            assert_eq!(parts, std_parts);
+        }
+    }
+
+    #[test]
+    fn test_split_edge_cases<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a,,b,");
+        let parts: Vec<_> = string.split(",").collect();
+        assert_eq!(parts, ["a", "", "b", ""]);
+        assert_eq!(parts[3].offset, 5..5);
+
+        let string: ImString<S> = ImString::new();
+        let parts: Vec<_> = string.split(",").collect();
+        assert_eq!(parts, [""]);
+    }
+
+    #[test]
+    fn test_splitn<S: Data<String>>(string: ImString<S>) {
+        for n in 0..5 {
+            for pattern in ["", " ", "\n", "o", "\u{fc}"] {
+                let parts: Vec<_> = string.splitn(n, pattern).collect();
+                let std_parts: Vec<_> = string.as_str().splitn(n, pattern).collect();
+                assert_eq!(parts, std_parts);
+            }
+        }
+        assert_eq!(string.splitn(0, " ").next(), None);
+        assert_eq!(string.splitn(1, " ").next(), Some(string.clone()));
+    }
+
+    #[test]
+    fn test_rsplitn<S: Data<String>>(string: ImString<S>) {
+        for n in 0..5 {
+            for pattern in ["", " ", "\n", "o", "\u{fc}"] {
+                let parts: Vec<_> = string.rsplitn(n, pattern).collect();
+                let std_parts: Vec<_> = string.as_str().rsplitn(n, pattern).collect();
+                assert_eq!(parts, std_parts);
+            }
+        }
+        assert_eq!(string.rsplitn(0, " ").next(), None);
+        assert_eq!(string.rsplitn(1, " ").next(), Some(string.clone()));
+    }
+
+    #[test]
+    fn test_splitn_remainder<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a::b::c");
+        let parts: Vec<_> = string.splitn(2, "::").collect();
+        assert_eq!(parts, ["a", "b::c"]);
+        let parts: Vec<_> = string.rsplitn(2, "::").collect();
+        assert_eq!(parts, ["c", "a::b"]);
+    }
+
+    #[test]
+    fn test_split_whitespace<S: Data<String>>(string: ImString<S>) {
+        let words: Vec<_> = string.split_whitespace().collect();
+        let std_words: Vec<_> = string.as_str().split_whitespace().collect();
+        assert_eq!(words, std_words);
+    }
+
+    #[test]
+    fn test_split_ascii_whitespace<S: Data<String>>(string: ImString<S>) {
+        let words: Vec<_> = string.split_ascii_whitespace().collect();
+        let std_words: Vec<_> = string.as_str().split_ascii_whitespace().collect();
+        assert_eq!(words, std_words);
+    }
+
+    #[test]
+    fn test_split_whitespace_kinds<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\t one  two\nthree\r\nfour\u{a0}five \n");
+        let words: Vec<_> = string.split_whitespace().collect();
+        assert_eq!(words, ["one", "two", "three", "four", "five"]);
+        let words: Vec<_> = string.split_ascii_whitespace().collect();
+        assert_eq!(words, ["one", "two", "three", "four\u{a0}five"]);
+        assert_eq!(string.slice(..2).split_whitespace().next(), None);
+    }
+
+    #[test]
+    fn test_split_at<S: Data<String>>(string: ImString<S>) {
+        for mid in 0..=string.len() {
+            if string.is_char_boundary(mid) {
+                let (left, right) = string.split_at(mid);
+                assert_eq!(left, string.as_str()[..mid]);
+                assert_eq!(right, string.as_str()[mid..]);
+                assert_eq!(format!("{left}{right}"), string.as_str());
+                assert_eq!(left.offset.end, right.offset.start);
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_split_at<S: Data<String>>(string: ImString<S>) {
+        for mid in 0..=string.len() + 1 {
+            let result = string.try_split_at(mid);
+            match string.as_str().get(..mid) {
+                Some(_) => assert_eq!(result, Some(string.split_at(mid))),
+                None => assert_eq!(result, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice_inclusive<S: Data<String>>(string: ImString<S>) {
+        for start in 0..string.len() {
+            for end in start..string.len() {
+                match string.as_str().get(start..=end) {
+                    Some(expected) => {
+                        assert_eq!(string.slice(start..=end), expected);
+                        assert_eq!(&string[start..=end], expected);
+                        assert_eq!(string.try_slice(..=end).unwrap(), string.as_str()[..=end]);
+                    }
+                    None => assert!(string.try_slice(start..=end).is_err()),
+                }
+            }
+        }
+        assert_eq!(
+            string.try_slice(..=string.len()),
+            Err(SliceError::EndOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_slice_inclusive_zero<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello");
+        assert_eq!(string.slice(0..=0), "h");
+        assert_eq!(&string[0..=0], "h");
+        assert_eq!(string.slice(0..=4), "hello");
+        assert_eq!(&string[0..=4], "hello");
+        assert_eq!(string.slice(1..=3), "ell");
+        assert_eq!(unsafe { string.slice_unchecked(0..=0) }, "h");
+
+        let string: ImString<S> = ImString::new();
+        assert_eq!(string.try_slice(0..=0), Err(SliceError::EndOutOfBounds));
+    }
+
+    #[test]
+    fn test_slice_overflow<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello");
+        let range = (Bound::Excluded(usize::MAX), Bound::Unbounded);
+        assert_eq!(string.try_slice(range), Err(SliceError::StartOutOfBounds));
+        let range = (Bound::Included(0), Bound::Included(usize::MAX));
+        assert_eq!(string.try_slice(range), Err(SliceError::EndOutOfBounds));
+    }
+
+    #[test]
+    fn test_split_off<S: Data<String>>(string: ImString<S>) {
+        for position in 0..=string.len() + 1 {
+            let mut left = string.clone();
+            match string.as_str().get(..position) {
+                Some(expected) => {
+                    let right = left.split_off(position);
+                    assert_eq!(left, expected);
+                    assert_eq!(right, string.as_str()[position..]);
+                    assert_eq!(left.offset.end, right.offset.start);
+                }
+                None => {
+                    assert_eq!(left.try_split_off(position), None);
+                    assert_eq!(left, string);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_off_sliced<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello world, goodbye");
+        let mut slice = string.slice(6..11);
+        assert_eq!(slice, "world");
+        assert_eq!(slice.try_split_off(6), None);
+        assert_eq!(slice.try_split_off(10), None);
+        let tail = slice.split_off(2);
+        assert_eq!(slice, "wo");
+        assert_eq!(tail, "rld");
+        assert_eq!(slice.offset, 6..8);
+        assert_eq!(tail.offset, 8..11);
+    }
+
+    #[test]
+    fn test_replace<S: Data<String>>(string: ImString<S>) {
+        for (from, to) in [("", "-"), ("o", "0"), ("lo", ""), ("\u{fc}", "ue"), ("xyz", "abc")] {
+            assert_eq!(string.replace(from, to), string.as_str().replace(from, to));
+        }
+    }
+
+    #[test]
+    fn test_replacen<S: Data<String>>(string: ImString<S>) {
+        for count in 0..4 {
+            for (from, to) in [("", "-"), ("o", "0"), ("lo", ""), ("xyz", "abc")] {
+                assert_eq!(
+                    string.replacen(from, to, count),
+                    string.as_str().replacen(from, to, count)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_replace_overlapping<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("aaaa");
+        assert_eq!(string.replace("aa", "b"), "bb");
+        assert_eq!(string.replacen("aa", "b", 1), "baa");
+        assert_eq!(string.replace("a", ""), "");
+    }
+
+    #[test]
+    fn test_to_lowercase<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.to_lowercase(), string.as_str().to_lowercase());
+        assert_eq!(string.to_ascii_lowercase(), string.as_str().to_ascii_lowercase());
+    }
+
+    #[test]
+    fn test_to_uppercase<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.to_uppercase(), string.as_str().to_uppercase());
+        assert_eq!(string.to_ascii_uppercase(), string.as_str().to_ascii_uppercase());
+    }
+
+    #[test]
+    fn test_case_expansion<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("stra\u{df}e");
+        assert_eq!(string.to_uppercase(), "STRASSE");
+        assert_eq!(
+            string.to_uppercase().chars().count(),
+            string.chars().count() + 1
+        );
+        assert_eq!(string.to_ascii_uppercase(), "STRA\u{df}E");
+
+        let string: ImString<S> = ImString::from("\u{130}stanbul");
+        assert_eq!(string.to_lowercase(), "i\u{307}stanbul");
+        assert_eq!(string.to_lowercase().len(), string.len() + 1);
+        assert_eq!(string.to_ascii_lowercase(), "\u{130}stanbul");
+    }
+
+    #[test]
+    fn test_as_mut_str<S: Data<String>>(string: ImString<S>) {
+        let original = string.as_str().to_string();
+        let mut string = string;
+        let clone = string.clone();
+        assert_eq!(string.as_mut_str(), original.as_str());
+        assert_eq!(string, original);
+        assert_eq!(clone, original);
+    }
+
+    #[test]
+    fn test_make_ascii_uppercase<S: Data<String>>(string: ImString<S>) {
+        let original = string.as_str().to_string();
+        let mut string = string;
+        let clone = string.clone();
+        string.make_ascii_uppercase();
+        assert_eq!(string, original.to_ascii_uppercase());
+        assert_eq!(clone, original);
+    }
+
+    #[test]
+    fn test_make_ascii_lowercase<S: Data<String>>(string: ImString<S>) {
+        let original = string.as_str().to_string();
+        let mut string = string;
+        let clone = string.clone();
+        string.make_ascii_lowercase();
+        assert_eq!(string, original.to_ascii_lowercase());
+        assert_eq!(clone, original);
+    }
+
+    #[test]
+    fn test_pop<S: Data<String>>(string: ImString<S>) {
+        let mut std_string = string.as_str().to_string();
+        let mut string = string;
+        loop {
+            let c = string.pop();
+            assert_eq!(c, std_string.pop());
+            assert_eq!(string, std_string);
+            assert!(string.string.get_borrowed().is_char_boundary(string.offset.end));
+            if c.is_none() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_pop_shared<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("a\u{e4}\u{1f600}");
+        let clone = string.clone();
+        assert_eq!(string.pop(), Some('\u{1f600}'));
+        assert_eq!(string.offset, 0..3);
+        assert_eq!(string.pop(), Some('\u{e4}'));
+        assert_eq!(string.offset, 0..1);
+        assert_eq!(clone, "a\u{e4}\u{1f600}");
+    }
+
+    #[test]
+    fn test_remove<S: Data<String>>(string: ImString<S>) {
+        for (index, c) in string.char_indices() {
+            let mut removed = string.clone();
+            let mut std_string = string.as_str().to_string();
+            assert_eq!(removed.remove(index), c);
+            std_string.remove(index);
+            assert_eq!(removed, std_string);
+        }
+    }
+
+    #[test]
+    fn test_remove_positions<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a\u{1f600}b");
+        let mut first = string.clone();
+        assert_eq!(first.remove(0), 'a');
+        assert_eq!(first, "\u{1f600}b");
+        let mut middle = string.clone();
+        assert_eq!(middle.remove(1), '\u{1f600}');
+        assert_eq!(middle, "ab");
+        let mut last = string.clone();
+        assert_eq!(last.remove(5), 'b');
+        assert_eq!(last, "a\u{1f600}");
+        assert_eq!(string, "a\u{1f600}b");
+    }
 
-impl<S: Data<String>> PartialEq<str> for ImString<S> {
-    fn eq(&self, other: &str) -> bool {
-        self.as_str().eq(other)
+    #[test]
+    fn test_drain<S: Data<String>>(string: ImString<S>) {
+        for start in 0..=string.len() {
+            for end in start..=string.len() {
+                if string.as_str().get(start..end).is_none() {
+                    continue;
+                }
+                let mut drained = string.clone();
+                let mut std_string = string.as_str().to_string();
+                let removed: String = drained.drain(start..end).collect();
+                let std_removed: String = std_string.drain(start..end).collect();
+                assert_eq!(removed, std_removed);
+                assert_eq!(drained, std_string);
+                assert_eq!(drained.offset.len(), std_string.len());
+            }
+        }
     }
-}
 
-impl<'a, S: Data<String>> PartialEq<&'a str> for ImString<S> {
-    fn eq(&self, other: &&'a str) -> bool {
-        self.as_str().eq(*other)
+    #[test]
+    fn test_drain_all<S: Data<String>>(string: ImString<S>) {
+        let original = string.as_str().to_string();
+        let mut string = string;
+        let removed: Vec<char> = string.drain(..).rev().collect();
+        assert_eq!(removed, original.chars().rev().collect::<Vec<_>>());
+        assert_eq!(string, "");
     }
-}
 
-impl<S: Data<String>> PartialEq<String> for ImString<S> {
-    fn eq(&self, other: &String) -> bool {
-        self.as_str().eq(other.as_str())
+    #[test]
+    fn test_drain_unconsumed<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("a\u{1f600}b\u{e4}c");
+        let mut drain = string.drain(1..8);
+        assert_eq!(drain.as_str(), "\u{1f600}b\u{e4}");
+        assert_eq!(drain.next(), Some('\u{1f600}'));
+        assert_eq!(drain.next_back(), Some('\u{e4}'));
+        assert_eq!(drain.as_str(), "b");
+        drop(drain);
+        assert_eq!(string, "ac");
+        assert_eq!(string.offset, 0..2);
     }
-}
 
-impl<S: Data<String>, O: Data<String>> PartialEq<ImString<O>> for ImString<S> {
-    fn eq(&self, other: &ImString<O>) -> bool {
-        self.as_str().eq(other.as_str())
+    #[test]
+    fn test_reserve<S: Data<String>>(string: ImString<S>) {
+        let original = string.clone();
+        let mut string = string;
+        string.reserve(16);
+        assert!(string.capacity() >= string.len() + 16);
+        assert_eq!(string, original);
+        assert_eq!(original, original.as_str());
+
+        let mut string = original.clone();
+        string.reserve_exact(16);
+        assert!(string.capacity() >= string.len() + 16);
+        assert_eq!(string, original);
     }
-}
 
-impl<S: Data<String>> Eq for ImString<S> {}
+    #[test]
+    fn test_try_reserve<S: Data<String>>(string: ImString<S>) {
+        let original = string.clone();
+        let mut string = string;
+        assert!(string.try_reserve(16).is_ok());
+        assert!(string.capacity() >= string.len() + 16);
+        assert_eq!(string, original);
+        assert!(string.try_reserve(usize::MAX).is_err());
+        assert_eq!(string, original);
+    }
 
-impl<S: Data<String>> PartialOrd<ImString<S>> for ImString<S> {
-    fn partial_cmp(&self, other: &ImString<S>) -> Option<Ordering> {
-        self.as_str().partial_cmp(other.as_str())
+    #[test]
+    fn test_reserve_sliced<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("hello world").slice(6..);
+        string.reserve(8);
+        assert!(string.capacity() >= 13);
+        string.push_str("s, hi");
+        assert_eq!(string, "worlds, hi");
     }
-}
 
-impl<S: Data<String>> Ord for ImString<S> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.as_str().cmp(other.as_str())
+    #[test]
+    fn test_shrink_to_fit<S: Data<String>>(string: ImString<S>) {
+        let original = string.as_str().to_string();
+        let mut string = string;
+        string.shrink_to_fit();
+        assert_eq!(string, original);
+        assert_eq!(string.offset, 0..original.len());
+        assert_eq!(string.string.get_borrowed().len(), original.len());
     }
-}
 
-impl<S: Data<String>> Debug for ImString<S> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
-        Debug::fmt(self.as_str(), f)
+    #[test]
+    fn test_shrink_to_fit_slice<S: Data<String>>() {
+        let document: ImString<S> = ImString::from("x".repeat(1024));
+        let mut string = document.slice(512..516);
+        drop(document);
+        let capacity = string.capacity();
+        string.shrink_to_fit();
+        assert_eq!(string, "xxxx");
+        assert!(string.capacity() < capacity);
+        assert_eq!(string.string.get_borrowed(), "xxxx");
     }
-}
 
-impl<S: Data<String>> Display for ImString<S> {
-    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), FmtError> {
-        Display::fmt(self.as_str(), formatter)
+    #[test]
+    fn test_shrink_to<S: Data<String>>() {
+        let document: ImString<S> = ImString::from("x".repeat(1024));
+        let mut string = document.slice(..4);
+        string.shrink_to(64);
+        assert_eq!(string, "xxxx");
+        assert!(string.capacity() >= 64);
+        assert!(string.capacity() < 1024);
+        assert_eq!(document.len(), 1024);
     }
-}
 
-impl<S: Data<String>> FromStr for ImString<S> {
-    type Err = Infallible;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(ImString::from(s))
+    #[test]
+    fn test_compact<S: Data<String>>(string: ImString<S>) {
+        let compact = string.compact();
+        assert_eq!(compact, string);
+        assert_eq!(compact.offset, 0..string.len());
+        assert_eq!(compact.string.get_borrowed().len(), string.len());
     }
-}
 
-// Delegate hash to contained str. This is important!
-impl<S: Data<String>> Hash for ImString<S> {
-    fn hash<H: Hasher>(&self, hasher: &mut H) {
-        self.as_str().hash(hasher)
+    #[test]
+    fn test_compact_slice<S: Data<String>>() {
+        let document: ImString<S> = ImString::from("hello, world!");
+        let string = document.slice(7..12);
+        let compact = string.compact();
+        assert_eq!(compact, "world");
+        assert_eq!(compact.string.get_borrowed().len(), 5);
+        assert_eq!(string.string.get_borrowed().len(), document.len());
     }
-}
 
-impl<S: Data<String>> Write for ImString<S> {
-    fn write_str(&mut self, string: &str) -> Result<(), FmtError> {
-        self.push_str(string);
-        Ok(())
+    #[test]
+    fn test_ref_count<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello world");
+        assert_eq!(string.ref_count(), 1);
+        assert!(string.is_unique());
+        let clone = string.clone();
+        let slice = string.slice(6..);
+        assert!(string.ref_count() >= 1);
+        assert_eq!(string.is_unique(), string.ref_count() == 1);
+        drop(clone);
+        drop(slice);
+        assert_eq!(string.ref_count(), 1);
+        assert!(string.is_unique());
     }
 
-    fn write_char(&mut self, c: char) -> Result<(), FmtError> {
-        self.push(c);
-        Ok(())
+    #[test]
+    fn test_into_bytes<S: Data<String>>(string: ImString<S>) {
+        let bytes = string.as_bytes().to_vec();
+        assert_eq!(string.clone().into_bytes(), bytes);
+        assert_eq!(string.into_bytes(), bytes);
     }
-}
 
-impl<S: Data<String>> Index<Range<usize>> for ImString<S> {
-    type Output = str;
-    fn index(&self, index: Range<usize>) -> &str {
-        &self.as_str()[index]
+    #[test]
+    fn test_from_utf16<S: Data<String>>() {
+        let string: ImString<S> = ImString::from_utf16(&[0x0068, 0x00e9, 0x006c, 0x006f]).unwrap();
+        assert_eq!(string, "h\u{e9}lo");
+        let string: ImString<S> = ImString::from_utf16(&[0xd83d, 0xde00]).unwrap();
+        assert_eq!(string, "\u{1f600}");
+        assert!(ImString::<S>::from_utf16(&[0x0061, 0xd83d]).is_err());
+        let string: ImString<S> = ImString::from_utf16_lossy(&[0x0061, 0xd83d, 0x0062]);
+        assert_eq!(string, "a\u{fffd}b");
     }
-}
 
-impl<S: Data<String>> Index<RangeFrom<usize>> for ImString<S> {
-    type Output = str;
-    fn index(&self, index: RangeFrom<usize>) -> &str {
-        &self.as_str()[index]
+    #[test]
+    fn test_split_once<S: Data<String>>(string: ImString<S>) {
+        for delimiter in ["a", "o", " ", "\u{1f600}", "ab", ""] {
+            let expected = string.as_str().split_once(delimiter);
+            let actual = string.split_once(delimiter);
+            assert_eq!(
+                actual.as_ref().map(|(l, r)| (l.as_str(), r.as_str())),
+                expected
+            );
+            let expected = string.as_str().rsplit_once(delimiter);
+            let actual = string.rsplit_once(delimiter);
+            assert_eq!(
+                actual.as_ref().map(|(l, r)| (l.as_str(), r.as_str())),
+                expected
+            );
+        }
     }
-}
 
-impl<S: Data<String>> Index<RangeFull> for ImString<S> {
-    type Output = str;
-    fn index(&self, index: RangeFull) -> &str {
-        &self.as_str()[index]
+    #[test]
+    fn test_split_once_edges<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("::key::value::");
+        let (left, right) = string.split_once("::").unwrap();
+        assert_eq!((left.as_str(), right.as_str()), ("", "key::value::"));
+        let (left, right) = string.rsplit_once("::").unwrap();
+        assert_eq!((left.as_str(), right.as_str()), ("::key::value", ""));
+        let (left, right) = string.slice(2..).split_once("::").unwrap();
+        assert_eq!((left.as_str(), right.as_str()), ("key", "value::"));
+        assert_eq!(right.offset, 7..14);
+        assert_eq!(string.split_once(":::"), None);
+        assert_eq!(string.rsplit_once("="), None);
     }
-}
 
-impl<S: Data<String>> Index<RangeInclusive<usize>> for ImString<S> {
-    type Output = str;
-    fn index(&self, index: RangeInclusive<usize>) -> &str {
-        &self.as_str()[index]
+    #[test]
+    fn test_lines_rev<S: Data<String>>(string: ImString<S>) {
+        let lines: Vec<_> = string.lines().rev().collect();
+        let std_lines: Vec<_> = string.as_str().lines().rev().collect();
+        assert_eq!(lines, std_lines);
     }
-}
 
-impl<S: Data<String>> Index<RangeTo<usize>> for ImString<S> {
-    type Output = str;
-    fn index(&self, index: RangeTo<usize>) -> &str {
-        &self.as_str()[index]
+    #[test]
+    fn test_char_slices<S: Data<String>>(string: ImString<S>) {
+        let slices: Vec<_> = string.char_slices().collect();
+        let indices: Vec<_> = string.as_str().char_indices().collect();
+        assert_eq!(slices.len(), indices.len());
+        for ((index, slice), (std_index, c)) in slices.iter().zip(indices.iter()) {
+            assert_eq!(index, std_index);
+            assert_eq!(slice.chars().collect::<Vec<_>>(), vec![*c]);
+            assert_eq!(slice.offset.start, string.offset.start + index);
+        }
+        let rebuilt: String = slices.iter().map(|(_, slice)| slice.as_str()).collect();
+        assert_eq!(rebuilt, string.as_str());
+        let reversed: Vec<_> = string.char_slices().rev().map(|(index, _)| index).collect();
+        let std_reversed: Vec<_> = string.as_str().char_indices().rev().map(|(i, _)| i).collect();
+        assert_eq!(reversed, std_reversed);
     }
-}
 
-pub type Lines<'a, S> = ImStringIterator<'a, S, std::str::Lines<'a>>;
+    #[test]
+    fn test_into_iter<S: Data<String>>(string: ImString<S>) {
+        let chars: Vec<char> = (&string).into_iter().collect();
+        assert_eq!(chars, string.as_str().chars().collect::<Vec<_>>());
+        let expected = string.as_str().to_string();
+        let mut owned = Vec::new();
+        for c in string {
+            owned.push(c);
+        }
+        assert_eq!(owned, expected.chars().collect::<Vec<_>>());
+    }
 
-pub struct ImStringIterator<'a, S: Data<String>, I: Iterator<Item = &'a str>> {
-    string: S,
-    iterator: I,
+    #[test]
+    fn test_into_iter_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a\u{e4}\u{1f600}b").slice(1..);
+        let mut chars = string.into_iter();
+        assert_eq!(chars.as_str(), "\u{e4}\u{1f600}b");
+        assert_eq!(chars.next(), Some('\u{e4}'));
+        assert_eq!(chars.next_back(), Some('b'));
+        assert_eq!(chars.next(), Some('\u{1f600}'));
+        assert_eq!(chars.next(), None);
+        assert_eq!(chars.next_back(), None);
+    }
+
+    #[test]
+    fn test_from_iter_imstring<S: Data<String>>() {
+        let string: ImString<S> = Vec::<ImString<S>>::new().into_iter().collect();
+        assert_eq!(string, "");
+        let string: ImString<S> = std::iter::empty::<ImString<S>>().sum();
+        assert_eq!(string, "");
+
+        let full: ImString<S> = ImString::from("hello");
+        let source: ImString<S> = ImString::from("big world!");
+        let pieces = vec![full.clone(), source.slice(3..4), source.slice(4..9)];
+        let string: ImString<S> = pieces.clone().into_iter().collect();
+        assert_eq!(string, "hello world");
+        let string: ImString<S> = pieces.into_iter().sum();
+        assert_eq!(string, "hello world");
+        assert_eq!(full, "hello");
+    }
+
+    #[test]
+    fn test_from_iter_string<S: Data<String>>() {
+        let string: ImString<S> = Vec::<String>::new().into_iter().collect();
+        assert_eq!(string, "");
+        let pieces = vec![String::from("h\u{e9}llo"), String::new(), String::from(" w")];
+        let string: ImString<S> = pieces.into_iter().collect();
+        assert_eq!(string, "h\u{e9}llo w");
+    }
+
+    #[test]
+    fn test_extend_owned<S: Data<String>>(string: ImString<S>) {
+        let original = string.as_str().to_string();
+        let other: ImString<S> = ImString::from("xyz\u{e4}");
+        let mut string = string;
+        string.extend(vec![String::from("ab"), String::new()]);
+        string.extend(vec![Cow::Borrowed("c"), Cow::Owned(String::from("d"))]);
+        string.extend(vec![other.slice(1..), other.slice(..1)]);
+        assert_eq!(string, format!("{original}abcdyz\u{e4}x"));
+        assert_eq!(other, "xyz\u{e4}");
+    }
+
+    #[test]
+    fn test_extend_from_vec<S: Data<String>>() {
+        let pieces: Vec<ImString<S>> = vec!["a", "", "bc", "\u{1f600}"]
+            .into_iter()
+            .map(ImString::from)
+            .collect();
+        let mut string: ImString<S> = ImString::new();
+        string.extend(pieces);
+        assert_eq!(string, "abc\u{1f600}");
+    }
+
+    #[test]
+    fn test_partial_ord_str<S: Data<String>>(string: ImString<S>) {
+        for other in ["", "a", "hello", "zzz", "\u{1f600}", string.as_str()] {
+            let expected = string.as_str().cmp(other);
+            let owned = other.to_string();
+            assert_eq!(string.partial_cmp(other), Some(expected));
+            assert_eq!(string.partial_cmp(&other), Some(expected));
+            assert_eq!(string.partial_cmp(&owned), Some(expected));
+            assert_eq!(other.partial_cmp(&string), Some(expected.reverse()));
+            assert_eq!(owned.partial_cmp(&string), Some(expected.reverse()));
+            assert_eq!(
+                string.cmp(&ImString::from(other)),
+                string.partial_cmp(other).unwrap()
+            );
+            assert_eq!(string == other, other == string);
+        }
+    }
 }
 
-impl<'a, S: Data<String>, I: Iterator<Item = &'a str>> Iterator for ImStringIterator<'a, S, I> {
-    type Item = ImString<S>;
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.iterator.next() {
-            Some(slice) => {
-                let offset =
-                    try_slice_offset(self.string.get().as_bytes(), slice.as_bytes()).unwrap();
-                Some(ImString {
-                    string: self.string.clone(),
-                    offset,
-                })
+tests! {
+    #[test]
+    fn test_from_conversions<S: Data<String>>(string: ImString<S>) {
+        let owned = string.as_str().to_string();
+        assert_eq!(ImString::<S>::from(&owned), owned);
+        assert_eq!(ImString::<S>::from(owned.clone().into_boxed_str()), owned);
+        assert_eq!(ImString::<S>::from(owned.chars().collect::<Vec<_>>()), owned);
+    }
+
+    #[test]
+    fn test_from_boxed_str_reuse<S: Data<String>>() {
+        let boxed: Box<str> = Box::from("h\u{e9}llo");
+        let pointer = boxed.as_ptr();
+        let string: ImString<S> = ImString::from(boxed);
+        assert_eq!(string, "h\u{e9}llo");
+        // strings which fit inline are copied instead
+        if string.heap_size() > 0 {
+            assert_eq!(string.as_ptr(), pointer);
+        }
+    }
+
+    #[test]
+    fn test_into_conversions<S: Data<String>>(string: ImString<S>) {
+        let expected = string.as_str().to_string();
+        let boxed: Box<str> = string.clone().into();
+        assert_eq!(&*boxed, expected);
+        let arc: Arc<str> = string.clone().into();
+        assert_eq!(&*arc, expected);
+        let rc: Rc<str> = string.clone().into();
+        assert_eq!(&*rc, expected);
+        let bytes: Vec<u8> = string.into();
+        assert_eq!(bytes, expected.as_bytes());
+    }
+
+    #[test]
+    fn test_into_boxed_str_slice<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello, world!");
+        let boxed: Box<str> = string.slice(7..12).into();
+        assert_eq!(&*boxed, "world");
+        assert_eq!(boxed.len(), 5);
+        let arc: Arc<str> = string.slice(..5).into();
+        assert_eq!(&*arc, "hello");
+        assert_eq!(string, "hello, world!");
+    }
+
+    #[test]
+    fn test_try_from_bytes<S: Data<String>>() {
+        for valid in ["hello", "h\u{e9}llo \u{1f600}", ""] {
+            let string = ImString::<S>::try_from(valid.as_bytes()).unwrap();
+            assert_eq!(string, valid);
+            let bytes = valid.as_bytes().to_vec();
+            let pointer = bytes.as_ptr();
+            let string = ImString::<S>::try_from(bytes).unwrap();
+            assert_eq!(string, valid);
+            // strings which fit inline are copied instead
+            if string.heap_size() > 0 {
+                assert_eq!(string.as_ptr(), pointer);
             }
-            None => None,
         }
+
+        let invalid: &[u8] = b"hello \xF0\x90\x80world";
+        let error = ImString::<S>::try_from(invalid).unwrap_err();
+        assert_eq!(error.utf8_error().valid_up_to(), 6);
+        assert_eq!(error.as_bytes(), invalid);
+        let error = ImString::<S>::try_from(invalid.to_vec()).unwrap_err();
+        assert_eq!(error.into_bytes(), invalid);
     }
-}
 
-impl<'a, S: Data<String>, I: Iterator<Item = &'a str>> ImStringIterator<'a, S, I> {
-    fn new(string: S, iterator: I) -> Self {
-        ImStringIterator { string, iterator }
+    #[test]
+    fn test_repeat<S: Data<String>>(string: ImString<S>) {
+        for n in 0..4 {
+            let repeated = string.repeat(n);
+            assert_eq!(repeated, string.as_str().repeat(n));
+            assert_eq!(repeated.len(), string.len() * n);
+        }
     }
-}
 
-impl<S: Data<String>> Deref for ImString<S> {
-    type Target = str;
+    #[test]
+    fn test_repeat_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("-\u{e4}\u{1f600}");
+        let repeated = string.repeat(5);
+        assert_eq!(repeated.len(), 35);
+        assert_eq!(repeated.chars().count(), 15);
+        assert_eq!(repeated.offset, 0..35);
+    }
 
-    fn deref(&self) -> &Self::Target {
-        self.as_str()
+    #[test]
+    fn test_matches<S: Data<String>>(string: ImString<S>) {
+        for pattern in ["a", "l", "ll", "\u{1f600}", " ", ""] {
+            let matches: Vec<_> = string.matches(pattern).collect();
+            let std_matches: Vec<_> = string.as_str().matches(pattern).collect();
+            assert_eq!(matches, std_matches);
+
+            let indices: Vec<_> = string.match_indices(pattern).collect();
+            let std_indices: Vec<_> = string.as_str().match_indices(pattern).collect();
+            assert_eq!(indices.len(), std_indices.len());
+            for ((index, slice), (std_index, std_slice)) in indices.iter().zip(std_indices) {
+                assert_eq!(*index, std_index);
+                assert_eq!(slice, std_slice);
+                assert_eq!(slice.offset.start, string.offset.start + index);
+            }
+        }
     }
-}
 
-impl<S: Data<String>> Borrow<str> for ImString<S> {
-    fn borrow(&self) -> &str {
-        self.as_str()
+    #[test]
+    fn test_matches_non_overlapping<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("aaaaa");
+        assert_eq!(string.matches("aa").count(), 2);
+        let indices: Vec<usize> = string.match_indices("aa").map(|(i, _)| i).collect();
+        assert_eq!(indices, [0, 2]);
+        let string: ImString<S> = ImString::from("\u{e4}b");
+        let indices: Vec<usize> = string.match_indices("").map(|(i, _)| i).collect();
+        assert_eq!(indices, [0, 2, 3]);
     }
-}
 
-impl<S: Data<String>> AsRef<str> for ImString<S> {
-    fn as_ref(&self) -> &str {
-        self.as_str()
+    #[test]
+    fn test_find_str<S: Data<String>>(string: ImString<S>) {
+        for pattern in ["a", "l", "ll", "\u{1f600}", " ", "", "not found"] {
+            let found = string.find_str(pattern);
+            let index = string.as_str().find(pattern);
+            assert_eq!(found.as_ref().map(|s| s.as_str()), index.map(|_| pattern));
+            if let (Some(found), Some(index)) = (found, index) {
+                assert_eq!(found.offset.start, string.offset.start + index);
+            }
+            let found = string.rfind_str(pattern);
+            let index = string.as_str().rfind(pattern);
+            assert_eq!(found.as_ref().map(|s| s.as_str()), index.map(|_| pattern));
+            if let (Some(found), Some(index)) = (found, index) {
+                assert_eq!(found.offset.start, string.offset.start + index);
+            }
+        }
     }
-}
 
-impl<S: Data<String>> AsRef<Path> for ImString<S> {
-    fn as_ref(&self) -> &Path {
-        self.as_str().as_ref()
+    #[test]
+    fn test_find_str_edges<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("start middle end");
+        assert_eq!(string.find_str("start").unwrap().offset, 0..5);
+        assert_eq!(string.rfind_str("end").unwrap().offset, 13..16);
+        assert_eq!(string.find_str("e").unwrap().offset, 11..12);
+        assert_eq!(string.rfind_str("e").unwrap().offset, 13..14);
+        assert_eq!(string.find_str("none"), None);
+        assert_eq!(string.rfind_str("none"), None);
+    }
+
+    #[test]
+    fn test_slice_chars<S: Data<String>>(string: ImString<S>) {
+        let chars: Vec<char> = string.chars().collect();
+        for start in 0..=chars.len() {
+            for end in start..=chars.len() {
+                let expected: String = chars[start..end].iter().collect();
+                assert_eq!(string.slice_chars(start..end), expected);
+                assert_eq!(string.try_slice_chars(start..end).unwrap(), expected);
+            }
+            let expected: String = chars[start..].iter().collect();
+            assert_eq!(string.slice_chars(start..), expected);
+        }
+        let count = chars.len();
+        assert_eq!(string.try_slice_chars(count + 1..), Err(SliceError::StartOutOfBounds));
+        assert_eq!(string.try_slice_chars(..count + 1), Err(SliceError::EndOutOfBounds));
+        assert_eq!(string.try_slice_chars(..=count), Err(SliceError::EndOutOfBounds));
+    }
+
+    #[test]
+    fn test_slice_chars_emoji<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a\u{1f600}\u{1f603}b\u{1f604}");
+        assert_eq!(string.slice_chars(1..3), "\u{1f600}\u{1f603}");
+        assert_eq!(string.slice_chars(1..3).offset, 1..9);
+        assert_eq!(string.slice_chars(4..=4), "\u{1f604}");
+        assert_eq!(string.slice_chars(..), string);
+        assert_eq!(string.slice(5..).slice_chars(1..), "b\u{1f604}");
+        assert_eq!(
+            string.try_slice_chars((Bound::Included(3), Bound::Excluded(2))),
+            Err(SliceError::EndBeforeStart)
+        );
+        assert_eq!(string.try_slice_chars(6..), Err(SliceError::StartOutOfBounds));
+        assert_eq!(string.try_slice_chars(2..usize::MAX), Err(SliceError::EndOutOfBounds));
+        assert_eq!(
+            string.try_slice_chars((Bound::Excluded(usize::MAX), Bound::Unbounded)),
+            Err(SliceError::StartOutOfBounds)
+        );
+        assert_eq!(
+            string.try_slice_chars(..=usize::MAX),
+            Err(SliceError::EndOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_char_boundary<S: Data<String>>(string: ImString<S>) {
+        let boundaries: Vec<usize> = string
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain([string.len()])
+            .collect();
+        for index in 0..string.len() + 4 {
+            let floor = string.floor_char_boundary(index);
+            let ceil = string.ceil_char_boundary(index);
+            let expected_floor = boundaries.iter().rev().find(|b| **b <= index).copied();
+            let expected_ceil = boundaries.iter().find(|b| **b >= index).copied();
+            assert_eq!(floor, expected_floor.unwrap());
+            assert_eq!(ceil, expected_ceil.unwrap_or(string.len()));
+            assert!(string.try_slice(..floor).is_ok());
+            assert!(string.try_slice(ceil..).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_char_boundary_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{e4}\u{1f600}").slice(2..);
+        for index in 1..4 {
+            assert_eq!(string.floor_char_boundary(index), 0);
+            assert_eq!(string.ceil_char_boundary(index), 4);
+        }
+        assert_eq!(string.floor_char_boundary(0), 0);
+        assert_eq!(string.ceil_char_boundary(4), 4);
+    }
+
+    #[test]
+    fn test_get<S: Data<String>>(string: ImString<S>) {
+        for start in 0..string.len() + 2 {
+            for end in 0..string.len() + 2 {
+                let range = (Bound::Included(start), Bound::Excluded(end));
+                assert_eq!(string.get(range), string.try_slice(range).ok());
+                assert_eq!(
+                    string.get(range).as_ref().map(ImString::as_str),
+                    string.as_str().get(range)
+                );
+            }
+            assert_eq!(string.get(start..), string.try_slice(start..).ok());
+            assert_eq!(string.get(..=start), string.try_slice(..=start).ok());
+        }
+    }
+
+    #[test]
+    fn test_escape<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.escape_debug(), string.as_str().escape_debug().to_string());
+        assert_eq!(string.escape_default(), string.as_str().escape_default().to_string());
+        assert_eq!(string.escape_unicode(), string.as_str().escape_unicode().to_string());
+    }
+
+    #[test]
+    fn test_escape_special<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("tab\t\"quote'\\\u{0}\u{7f}\u{e9}\u{301}");
+        assert_eq!(
+            string.escape_debug(),
+            "tab\\t\\\"quote\\'\\\\\\0\\u{7f}\u{e9}\u{301}"
+        );
+        assert_eq!(
+            string.escape_default(),
+            "tab\\t\\\"quote\\'\\\\\\u{0}\\u{7f}\\u{e9}\\u{301}"
+        );
+        assert_eq!(string.slice(..3).escape_unicode(), "\\u{74}\\u{61}\\u{62}");
+    }
+
+    #[test]
+    fn test_parse<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("12 -3.5 x");
+        assert_eq!(string.slice(..2).parse::<u32>(), Ok(12));
+        assert_eq!(string.slice(3..7).parse::<f64>(), Ok(-3.5));
+        assert_eq!(string.slice(3..5).parse::<i8>(), Ok(-3));
+        let error = string.slice(8..).parse::<u32>().unwrap_err();
+        assert_eq!(error, "x".parse::<u32>().unwrap_err());
+        assert!(string.parse::<u32>().is_err());
+        assert_eq!(string.parse::<String>().unwrap(), "12 -3.5 x");
+    }
+
+    #[test]
+    fn test_split_terminator<S: Data<String>>(string: ImString<S>) {
+        for pattern in ["l", "o", " ", "\u{1f600}", "ll", ""] {
+            let parts: Vec<_> = string.split_terminator(pattern).collect();
+            let std_parts: Vec<_> = string.as_str().split_terminator(pattern).collect();
+            assert_eq!(parts, std_parts);
+            let parts: Vec<_> = string.rsplit_terminator(pattern).collect();
+            let std_parts: Vec<_> = string.as_str().rsplit_terminator(pattern).collect();
+            assert_eq!(parts, std_parts);
+            let parts: Vec<_> = string.split_inclusive(pattern).collect();
+            let std_parts: Vec<_> = string.as_str().split_inclusive(pattern).collect();
+            assert_eq!(parts, std_parts);
+        }
+    }
+
+    #[test]
+    fn test_split_terminator_trailing<S: Data<String>>() {
+        for input in ["a;b;", "a;b", ";", "", "a;;"] {
+            let string: ImString<S> = ImString::from(input);
+            let parts: Vec<_> = string.split_terminator(";").collect();
+            assert_eq!(parts, input.split_terminator(';').collect::<Vec<_>>());
+            let parts: Vec<_> = string.rsplit_terminator(";").collect();
+            assert_eq!(parts, input.rsplit_terminator(';').collect::<Vec<_>>());
+            let parts: Vec<_> = string.split_inclusive(";").collect();
+            assert_eq!(parts, input.split_inclusive(';').collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_lines_with_terminators<S: Data<String>>(string: ImString<S>) {
+        let lines: Vec<_> = string.lines_with_terminators().collect();
+        assert_eq!(lines.concat(), string.as_str());
+        let stripped: Vec<_> = lines
+            .iter()
+            .map(|line| match line.as_str().strip_suffix("\n") {
+                Some(line) => line.strip_suffix("\r").unwrap_or(line),
+                None => line.as_str(),
+            })
+            .collect();
+        let std_lines: Vec<_> = string.as_str().lines().collect();
+        assert_eq!(stripped, std_lines);
+    }
+
+    #[test]
+    fn test_lines_with_terminators_mixed<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("first\nsecond\r\n\nlast");
+        let lines: Vec<_> = string.lines_with_terminators().collect();
+        assert_eq!(lines, ["first\n", "second\r\n", "\n", "last"]);
+        assert_eq!(lines[1].offset, 6..14);
+        let lines: Vec<_> = string.lines_with_terminators().rev().collect();
+        assert_eq!(lines, ["last", "\n", "second\r\n", "first\n"]);
+        let string: ImString<S> = ImString::from("trailing\n");
+        let lines: Vec<_> = string.lines_with_terminators().collect();
+        assert_eq!(lines, ["trailing\n"]);
+    }
+
+    #[test]
+    fn test_append<S: Data<String>>(string: ImString<S>) {
+        let other: ImString<S> = ImString::from("big w\u{f6}rld");
+        let expected = format!("{}{}", string.as_str(), &other.as_str()[3..]);
+        let mut appended = string.clone();
+        appended.append(&other.slice(3..));
+        assert_eq!(appended, expected);
+        let mut appended = string;
+        appended += other.slice(3..);
+        assert_eq!(appended, expected);
+        assert_eq!(other, "big w\u{f6}rld");
+    }
+
+    #[test]
+    fn test_append_self<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("ab");
+        let clone = string.clone();
+        string.append(&clone);
+        string += clone.slice(1..);
+        assert_eq!(string, "ababb");
+        assert_eq!(clone, "ab");
+    }
+
+    #[test]
+    fn test_add_mixed<S: Data<String>>(string: ImString<S>) {
+        let other: ImString<S> = ImString::from("xyz");
+        let expected = format!("{}a-b\u{e4}yz!", string.as_str());
+        let result = string.clone() + "a" + '-' + String::from("b\u{e4}") + other.slice(1..) + '!';
+        assert_eq!(result, expected);
+
+        let mut result = string;
+        result += "a";
+        result += '-';
+        result += String::from("b\u{e4}");
+        result += other.slice(1..);
+        result += '!';
+        assert_eq!(result, expected);
+        assert_eq!(other, "xyz");
+    }
+
+    #[test]
+    fn test_eq_bytes<S: Data<String>>(string: ImString<S>) {
+        let bytes = string.as_bytes().to_vec();
+        assert!(string == bytes);
+        assert!(string == bytes.as_slice());
+        assert!(string == *bytes.as_slice());
+        let mut different = bytes.clone();
+        different.push(b'!');
+        assert!(string != different);
+        assert!(string != different.as_slice());
+    }
+
+    #[test]
+    fn test_eq_bytes_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("h\u{e9}llo \u{1f600}");
+        assert_eq!(string, "h\u{e9}llo \u{1f600}".as_bytes().to_vec());
+        assert_eq!(string, b"h\xc3\xa9llo \xf0\x9f\x98\x80".as_slice());
+        let empty: ImString<S> = ImString::new();
+        assert_eq!(empty, Vec::<u8>::new());
+        assert_eq!(empty, b"".as_slice());
+        assert_ne!(empty, b"\0".as_slice());
+    }
+
+    #[test]
+    fn test_byte_key<S: Data<String>>(string: ImString<S>) {
+        let key = ByteKey(string.clone());
+        let bytes: &[u8] = Borrow::borrow(&key);
+        assert_eq!(bytes, string.as_bytes());
+        let mut map = std::collections::HashMap::new();
+        map.insert(key, 1);
+        map.insert(ByteKey(ImString::from("\u{1f600}")), 2);
+        let expected = if string == "\u{1f600}" { 2 } else { 1 };
+        assert_eq!(map.get(string.as_bytes()), Some(&expected));
+        assert_eq!(map.get("\u{1f600}".as_bytes()), Some(&2));
+        assert_eq!(map.get(b"missing".as_slice()), None);
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(ByteKey(string.clone()), 1);
+        assert_eq!(map.get(string.as_bytes()), Some(&1));
+    }
+
+    #[test]
+    fn test_to_threadsafe_local<S: Data<String>>(string: ImString<S>) {
+        let threadsafe = string.to_threadsafe();
+        assert_eq!(threadsafe, string);
+        assert_eq!(threadsafe.offset, 0..string.len());
+        assert_eq!(threadsafe.string.len(), string.len());
+        assert_eq!(Arc::strong_count(&threadsafe.string), 1);
+        let local = string.to_local();
+        assert_eq!(local, string);
+        assert_eq!(local.offset, 0..string.len());
+        assert_eq!(local.string.len(), string.len());
+        assert_eq!(Rc::strong_count(&local.string), 1);
+    }
+
+    #[test]
+    fn test_reader<S: Data<String>>(string: ImString<S>) {
+        for size in [1, 2, 3, 7, 64] {
+            let mut reader = string.reader();
+            let mut output = Vec::new();
+            let mut buffer = vec![0; size];
+            loop {
+                let length = reader.read(&mut buffer).unwrap();
+                if length == 0 {
+                    break;
+                }
+                assert!(length <= size);
+                output.extend_from_slice(&buffer[..length]);
+            }
+            assert_eq!(output, string.as_bytes());
+            assert_eq!(reader.read(&mut buffer).unwrap(), 0);
+        }
     }
-}
 
-impl<S: Data<String>> AsRef<OsStr> for ImString<S> {
-    fn as_ref(&self) -> &OsStr {
-        self.as_str().as_ref()
+    #[test]
+    fn test_reader_buf_read<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("first\nsecond\r\nthird");
+        let lines: Vec<String> = string.slice(1..).reader().lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["irst", "second", "third"]);
+        let mut output = String::new();
+        string.reader().read_to_string(&mut output).unwrap();
+        assert_eq!(output, string);
     }
-}
 
-impl<S: Data<String>> AsRef<[u8]> for ImString<S> {
-    fn as_ref(&self) -> &[u8] {
-        self.as_str().as_ref()
+    #[test]
+    fn test_reader_partial_char<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a\u{1f600}b");
+        let mut reader = string.slice(1..).reader();
+        reader.consume(2);
+        assert_eq!(reader.fill_buf().unwrap(), b"\x98\x80b");
+        let mut buffer = [0; 8];
+        assert_eq!(reader.read(&mut buffer).unwrap(), 3);
+        assert_eq!(&buffer[..3], b"\x98\x80b");
+        reader.consume(1);
+        assert_eq!(reader.fill_buf().unwrap(), b"");
     }
-}
 
-impl<S: Data<String>> ToSocketAddrs for ImString<S> {
-    type Iter = <String as ToSocketAddrs>::Iter;
-    fn to_socket_addrs(&self) -> std::io::Result<<String as ToSocketAddrs>::Iter> {
-        self.as_str().to_socket_addrs()
+    #[test]
+    fn test_common_prefix_suffix<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.common_prefix(&string), string);
+        assert_eq!(string.common_suffix(&string), string);
+        assert_eq!(string.common_prefix(""), "");
+        assert_eq!(string.common_suffix(""), "");
+        let other = format!("{string}!");
+        assert_eq!(string.common_prefix(&other), string);
+        let other = format!("!{string}");
+        assert_eq!(string.common_suffix(&other), string);
     }
-}
 
-impl<S: Data<String>> Add<&str> for ImString<S> {
-    type Output = ImString<S>;
-    fn add(mut self, string: &str) -> Self::Output {
-        self.push_str(string);
-        self
+    #[test]
+    fn test_common_prefix_suffix_edges<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("h\u{e9}llo w\u{f6}rld");
+        assert_eq!(string.common_prefix("abc"), "");
+        assert_eq!(string.common_suffix("abc"), "");
+        assert_eq!(string.common_prefix("h\u{e9}lp"), "h\u{e9}l");
+        assert_eq!(string.common_suffix("w\u{f6}rld"), "w\u{f6}rld");
+        assert_eq!(string.common_suffix("w\u{f6}rld").offset, 7..13);
+        // \u{e9} and \u{e8} share their first byte, the prefix must not split the character.
+        assert_eq!(string.common_prefix("h\u{e8}llo"), "h");
+        // \u{f6} and \u{d6} share their first byte, the suffix must not split the character.
+        assert_eq!(string.common_suffix("\u{d6}rld"), "rld");
+        let slice = string.slice(1..);
+        assert_eq!(slice.common_prefix("\u{e9}l"), "\u{e9}l");
+        assert_eq!(slice.common_prefix("\u{e9}l").offset, 1..4);
     }
-}
 
-impl<S: Data<String>> AddAssign<&str> for ImString<S> {
-    fn add_assign(&mut self, string: &str) {
-        self.push_str(string);
+    #[test]
+    fn test_full<S: Data<String>>(string: ImString<S>) {
+        let full = string.full();
+        assert_eq!(full, string.string.get_borrowed());
+        assert_eq!(full.offset, 0..string.string.get_borrowed().len());
+        assert_eq!(&full[string.parent_range()], string);
+        assert_eq!(string.parent_range(), string.raw_offset());
+        for start in (0..=string.len()).filter(|i| string.is_char_boundary(*i)) {
+            let slice = string.slice(start..);
+            assert_eq!(slice.full(), full);
+            assert_eq!(&slice.full()[slice.parent_range()], slice);
+            assert_eq!(slice.slice(..0).full(), full);
+        }
     }
-}
 
-impl<S: Data<String>> Extend<char> for ImString<S> {
-    fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
-        unsafe {
-            self.unchecked_append(|mut string| {
-                string.extend(iter);
-                string
-            });
+    #[test]
+    fn test_byte_offset_in<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.byte_offset_in(&string), Some(0..string.len()));
+        for start in (0..=string.len()).filter(|i| string.is_char_boundary(*i)) {
+            let slice = string.slice(start..);
+            // slicing a string with cloned or inline storage does not share the data
+            let shared = slice.string.get_borrowed().as_ptr() == string.string.get_borrowed().as_ptr();
+            let expected = shared.then_some(start..string.len());
+            assert_eq!(slice.byte_offset_in(&string), expected);
+            if let Some(offset) = slice.byte_offset_in(&slice.full()) {
+                assert_eq!(&slice.full()[offset], slice);
+            }
         }
     }
-}
 
-impl<'a, S: Data<String>> Extend<&'a char> for ImString<S> {
-    fn extend<T: IntoIterator<Item = &'a char>>(&mut self, iter: T) {
-        unsafe {
-            self.unchecked_append(|mut string| {
-                string.extend(iter);
-                string
-            });
+    #[test]
+    fn test_byte_offset_in_edges<S: Data<String>>() {
+        let root: ImString<S> = ImString::from("fn main() {}");
+        let name = root.slice(3..7);
+        assert_eq!(name.byte_offset_in(&name), Some(0..4));
+        let unrelated: ImString<S> = ImString::from("fn main() {}");
+        assert_eq!(name.byte_offset_in(&unrelated), None);
+        assert_eq!(unrelated.byte_offset_in(&root), None);
+    }
+
+    #[test]
+    fn test_take_advance<S: Data<String>>(string: ImString<S>) {
+        for position in 0..=string.len() + 1 {
+            let mut taken = string.clone();
+            let mut advanced = string.clone();
+            match string.as_str().get(..position) {
+                Some(expected) => {
+                    assert_eq!(taken.take(position), expected);
+                    assert_eq!(taken, string.as_str()[position..]);
+                    advanced.advance(position);
+                    assert_eq!(advanced, taken);
+                }
+                None => {
+                    assert!(taken.try_take(position).is_err());
+                    assert!(advanced.try_advance(position).is_err());
+                    assert_eq!(taken, string);
+                    assert_eq!(advanced, string);
+                }
+            }
         }
     }
-}
 
-impl<'a, S: Data<String>> Extend<&'a str> for ImString<S> {
-    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
-        unsafe {
-            self.unchecked_append(|mut string| {
-                string.extend(iter);
-                string
-            });
+    #[test]
+    fn test_take_chars<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("a\u{e9}\u{20ac}\u{1f600}");
+        assert_eq!(string.try_take(2), Err(SliceError::EndNotAligned));
+        assert_eq!(string.try_take(11), Err(SliceError::EndOutOfBounds));
+        assert_eq!(string.try_advance(2), Err(SliceError::StartNotAligned));
+        assert_eq!(string.try_advance(11), Err(SliceError::StartOutOfBounds));
+        let mut chars = Vec::new();
+        while let Some(c) = string.chars().next() {
+            chars.push(string.take(c.len_utf8()));
         }
+        assert_eq!(chars, ["a", "\u{e9}", "\u{20ac}", "\u{1f600}"]);
+        assert_eq!(chars[2].offset, 3..6);
+        assert!(string.is_empty());
+        assert_eq!(string.offset, 10..10);
+
+        let mut string: ImString<S> = ImString::from("\u{e9}t\u{e9}");
+        string.advance(2);
+        assert_eq!(string, "t\u{e9}");
+        string.advance(1);
+        assert_eq!(string, "\u{e9}");
+        assert_eq!(string.offset, 3..5);
     }
-}
 
-impl<S: Data<String>> FromIterator<char> for ImString<S> {
-    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
-        let mut string = ImString::new();
-        string.extend(iter);
-        string
+    #[test]
+    fn test_trim_matches<S: Data<String>>(string: ImString<S>) {
+        for pattern in ['a', 'o', '/', '\u{1f600}'] {
+            let padded = ImString::<S>::from(format!("{pattern}{pattern}{string}{pattern}"));
+            for string in [string.clone(), padded] {
+                let trimmed = string.trim_matches(pattern);
+                assert_eq!(trimmed, string.as_str().trim_matches(pattern));
+                assert!(trimmed.offset.start >= string.offset.start);
+                assert!(trimmed.offset.end <= string.offset.end);
+                let pattern = pattern.to_string();
+                let trimmed = string.trim_start_matches(&pattern);
+                assert_eq!(trimmed, string.as_str().trim_start_matches(&pattern));
+                assert_eq!(trimmed.offset.end, string.offset.end);
+                let trimmed = string.trim_end_matches(&pattern);
+                assert_eq!(trimmed, string.as_str().trim_end_matches(&pattern));
+                assert_eq!(trimmed.offset.start, string.offset.start);
+            }
+        }
     }
-}
 
-impl<'a, S: Data<String>> FromIterator<&'a char> for ImString<S> {
-    fn from_iter<T: IntoIterator<Item = &'a char>>(iter: T) -> Self {
-        let mut string = ImString::new();
-        string.extend(iter);
-        string
+    #[test]
+    fn test_trim_matches_patterns<S: Data<String>>() {
+        let path: ImString<S> = ImString::from("///usr//lib///");
+        assert_eq!(path.trim_matches('/'), "usr//lib");
+        assert_eq!(path.trim_matches('/').offset, 3..11);
+        assert_eq!(path.trim_start_matches("/"), "usr//lib///");
+        assert_eq!(path.trim_end_matches("/"), "///usr//lib");
+        assert_eq!(path.trim_start_matches("//"), "/usr//lib///");
+        assert_eq!(path.trim_end_matches("//"), "///usr//lib/");
+
+        let string: ImString<S> = ImString::from("abababcab");
+        assert_eq!(string.trim_start_matches("ab"), "cab");
+        assert_eq!(string.trim_start_matches("ab").offset, 6..9);
+        assert_eq!(string.trim_end_matches("ab"), "abababc");
+        assert_eq!(string.trim_start_matches("ba"), string);
+        assert_eq!(string.trim_start_matches(""), string);
+        assert_eq!(string.slice(6..).trim_end_matches("cab"), "");
     }
-}
 
-impl<'a, S: Data<String>> FromIterator<&'a str> for ImString<S> {
-    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
-        let mut string = ImString::new();
-        string.extend(iter);
-        string
+    #[test]
+    fn test_reverse<S: Data<String>>(string: ImString<S>) {
+        let reversed = string.reverse();
+        assert_eq!(reversed, string.chars().rev().collect::<String>());
+        assert_eq!(reversed.len(), string.len());
+        assert_eq!(reversed.reverse(), string);
     }
-}
 
-#[cfg(test)]
-fn test_strings<S: Data<String>>() -> Vec<ImString<S>> {
-    let long = ImString::from("long string here");
-    let world = ImString::from("world");
-    let some = ImString::from("some");
-    let multiline = ImString::from("some\nmulti\nline\nstring\nthat\nis\nlong");
-    vec![
-        ImString::new(),
-        ImString::default(),
-        ImString::from("hello"),
-        ImString::from("0.0.0.0:800"),
-        ImString::from("localhost:1234"),
-        long.clone(),
-        long.slice(4..10),
-        long.slice(0..4),
-        long.slice(4..4),
-        long.slice(5..),
-        long.slice(..),
-        world.clone(),
-        world.clone(),
-        some.slice(4..),
-        some,
-        multiline.slice(5..15),
-        multiline,
-        ImString::from("\u{e4}\u{fc}\u{f6}\u{f8}\u{3a9}"),
-        ImString::from("\u{1f600}\u{1f603}\u{1f604}"),
-        ImString::from("o\u{308}u\u{308}a\u{308}"),
-    ]
-}
+    #[test]
+    fn test_reverse_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a\u{e9}\u{20ac}\u{1f600}");
+        assert_eq!(string.reverse(), "\u{1f600}\u{20ac}\u{e9}a");
+        assert_eq!(string.slice(1..6).reverse(), "\u{20ac}\u{e9}");
+        assert_eq!(string.reverse().offset, 0..10);
+        // combining marks are reversed as individual chars and end up before their base
+        let string: ImString<S> = ImString::from("ae\u{301}b");
+        assert_eq!(string.reverse(), "b\u{301}ea");
+        let palindrome: ImString<S> = ImString::from("r\u{e4}r");
+        assert_eq!(palindrome.reverse(), palindrome);
+    }
 
-macro_rules! tests {
-    () => {};
-    (#[test] fn $name:ident <S: Data<String>>() $body:tt $($rest:tt)*) => {
-        #[test]
-        fn $name() {
-            fn $name <S: Data<String>>() $body
-            $name::<Threadsafe>();
-            $name::<Local>();
-            $name::<Cloned<String>>();
-            $name::<Box<String>>();
-        }
-        tests!{$($rest)*}
-    };
-    (#[test] fn $name:ident <S: Data<String>>($string:ident: ImString<S>) $body:tt $($rest:tt)*) => {
-        #[test]
-        fn $name() {
-            fn $name <S: Data<String>>() {
-                fn $name <S: Data<String>>($string: ImString<S>) $body
-                for string in test_strings::<S>().into_iter() {
-                    $name(string);
-                }
-            }
-            $name::<Threadsafe>();
-            $name::<Local>();
-            $name::<Cloned<String>>();
-            $name::<Box<String>>();
+    #[test]
+    fn test_pad<S: Data<String>>(string: ImString<S>) {
+        let length = string.chars().count();
+        for width in [0, length, length + 1, length + 4] {
+            let s = string.as_str();
+            assert_eq!(string.pad_start(width, '-'), format!("{s:->width$}"));
+            assert_eq!(string.pad_end(width, '-'), format!("{s:-<width$}"));
+            assert_eq!(string.center(width, '-'), format!("{s:-^width$}"));
         }
-        tests!{$($rest)*}
+        assert_eq!(string.pad_start(length, '-').offset, string.offset);
+        assert_eq!(string.pad_end(length, '-').offset, string.offset);
+        assert_eq!(string.center(length, '-').offset, string.offset);
     }
-}
 
-tests! {
     #[test]
-    fn test_new<S: Data<String>>() {
-        let string: ImString<S> = ImString::new();
-        assert_eq!(string.string.get().len(), 0);
-        assert_eq!(string.offset, 0..0);
+    fn test_pad_edges<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{e9}t\u{e9}");
+        assert_eq!(string.pad_start(3, ' '), "\u{e9}t\u{e9}");
+        assert_eq!(string.pad_start(5, ' '), "  \u{e9}t\u{e9}");
+        assert_eq!(string.pad_end(4, ' '), "\u{e9}t\u{e9} ");
+        assert_eq!(string.center(5, ' '), " \u{e9}t\u{e9} ");
+        assert_eq!(string.center(6, ' '), " \u{e9}t\u{e9}  ");
+        assert_eq!(string.center(7, ' '), "  \u{e9}t\u{e9}  ");
+        let padded = string.pad_end(5, '\u{2500}');
+        assert_eq!(padded, "\u{e9}t\u{e9}\u{2500}\u{2500}");
+        assert_eq!(padded.len(), 11);
+        assert_eq!(padded.chars().count(), 5);
+        let empty: ImString<S> = ImString::new();
+        assert_eq!(empty.center(3, '\u{1f600}'), "\u{1f600}\u{1f600}\u{1f600}");
     }
 
     #[test]
-    fn test_default<S: Data<String>>() {
-        let string: ImString<S> = ImString::new();
-        assert_eq!(string.string.get().len(), 0);
-        assert_eq!(string.offset, 0..0);
+    fn test_concat_join<S: Data<String>>(string: ImString<S>) {
+        let parts = [string.clone(), string.slice(..0), string.clone()];
+        let std_parts = [string.as_str(), "", string.as_str()];
+        assert_eq!(ImString::concat(&parts), std_parts.concat());
+        for separator in ["", ",", "\u{2014}"] {
+            let joined = ImString::join(separator, &parts);
+            assert_eq!(joined, std_parts.join(separator));
+            assert_eq!(joined.offset, 0..joined.len());
+            let inline = joined.string.inline_capacity();
+            assert_eq!(joined.capacity(), joined.len().max(inline));
+        }
     }
 
     #[test]
-    fn test_with_capacity<S: Data<String>>() {
-        for capacity in [10, 100, 256] {
-            let string: ImString<S> = ImString::with_capacity(capacity);
-            assert!(string.capacity() >= capacity);
-            assert_eq!(string.string.get().len(), 0);
-            assert_eq!(string.offset, 0..0);
-        }
+    fn test_concat_join_edges<S: Data<String>>() {
+        let empty: [ImString<S>; 0] = [];
+        assert_eq!(ImString::concat(&empty), "");
+        assert_eq!(ImString::join(", ", &empty), "");
+        let single: [ImString<S>; 1] = [ImString::from("hello world").slice(6..)];
+        assert_eq!(ImString::concat(&single), "world");
+        assert_eq!(ImString::join(", ", &single), "world");
+        let parts: [ImString<S>; 3] = ["\u{e9}", "b", "c"].map(ImString::from);
+        assert_eq!(ImString::concat(&parts), "\u{e9}bc");
+        assert_eq!(ImString::join("\u{1f600}", &parts), "\u{e9}\u{1f600}b\u{1f600}c");
+        assert_eq!(ImString::join("\u{1f600}", &parts).len(), 12);
     }
 
     #[test]
-    fn test_offset<S: Data<String>>(string: ImString<S>) {
-        assert!(string.offset.start <= string.string.get().len());
-        assert!(string.offset.start <= string.offset.end);
-        assert!(string.offset.end <= string.string.get().len());
+    fn test_eq_ignore_ascii_case<S: Data<String>>(string: ImString<S>) {
+        let upper = string.to_ascii_uppercase();
+        let lower = string.to_ascii_lowercase();
+        assert!(string.eq_ignore_ascii_case(&upper));
+        assert!(upper.eq_ignore_ascii_case(&lower));
+        assert_eq!(CaseInsensitive(upper.clone()), CaseInsensitive(lower.clone()));
+        use std::hash::BuildHasher;
+        let state = std::collections::hash_map::RandomState::new();
+        assert_eq!(
+            state.hash_one(CaseInsensitive(upper)),
+            state.hash_one(CaseInsensitive(lower))
+        );
+        let longer = ImString::<S>::from(format!("{string}x"));
+        assert!(!string.eq_ignore_ascii_case(&longer));
+        assert_ne!(CaseInsensitive(string), CaseInsensitive(longer));
     }
 
     #[test]
-    fn test_as_str<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.as_str(), &string.string.get()[string.offset.clone()]);
-        assert_eq!(string.as_str().len(), string.len());
+    fn test_case_insensitive_map<S: Data<String>>() {
+        let mut map = std::collections::HashMap::new();
+        map.insert(CaseInsensitive(ImString::<S>::from("Hello")), 1);
+        assert_eq!(map.get(&CaseInsensitive(ImString::from("HELLO"))), Some(&1));
+        assert_eq!(map.get(&CaseInsensitive(ImString::from("hello"))), Some(&1));
+        assert_eq!(map.get(&CaseInsensitive(ImString::from("hell"))), None);
+        assert_eq!(map.get(&CaseInsensitive(ImString::from("H\u{c9}LLO"))), None);
+        map.insert(CaseInsensitive(ImString::from("say hello").slice(4..)), 2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&CaseInsensitive(ImString::from("hElLo"))), Some(&2));
     }
 
     #[test]
-    fn test_as_bytes<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.as_bytes(), &string.string.get().as_bytes()[string.offset.clone()]);
-        assert_eq!(string.as_bytes().len(), string.len());
+    fn test_char_len<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.char_len(), string.as_str().chars().count());
+        assert!(string.char_len() <= string.len());
     }
 
     #[test]
-    fn test_len<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.len(), string.offset.len());
-        assert_eq!(string.len(), string.as_str().len());
-        assert_eq!(string.len(), string.as_bytes().len());
+    fn test_char_len_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("ascii only");
+        assert_eq!(string.char_len(), string.len());
+        let string: ImString<S> = ImString::from("a\u{e9}\u{20ac}\u{1f600}");
+        assert_eq!(string.len(), 10);
+        assert_eq!(string.char_len(), 4);
+        assert_eq!(string.slice(1..6).char_len(), 2);
+        let string: ImString<S> = ImString::from("e\u{301}");
+        assert_eq!(string.char_len(), 2);
     }
 
     #[test]
-    fn test_clear<S: Data<String>>(string: ImString<S>) {
-        let mut string = string;
-        string.clear();
-        assert_eq!(string.as_str(), "");
-        assert_eq!(string.len(), 0);
+    fn test_char_at<S: Data<String>>(string: ImString<S>) {
+        let chars: Vec<_> = string.as_str().char_indices().collect();
+        for (char_index, (byte_index, c)) in chars.iter().enumerate() {
+            assert_eq!(string.char_at(char_index), Some(*c));
+            assert_eq!(string.byte_index_of_char(char_index), Some(*byte_index));
+        }
+        assert_eq!(string.char_at(chars.len()), None);
+        assert_eq!(string.byte_index_of_char(chars.len()), Some(string.len()));
+        assert_eq!(string.byte_index_of_char(chars.len() + 1), None);
     }
 
     #[test]
-    fn test_debug<S: Data<String>>(string: ImString<S>) {
-        let debug_string = format!("{string:?}");
-        let debug_str = format!("{:?}", string.as_str());
-        assert_eq!(debug_string, debug_str);
+    fn test_char_at_emoji<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("a\u{1f600}b\u{1f44d}\u{1f3fd}c");
+        assert_eq!(string.char_at(0), Some('a'));
+        assert_eq!(string.char_at(1), Some('\u{1f600}'));
+        assert_eq!(string.char_at(2), Some('b'));
+        assert_eq!(string.char_at(4), Some('\u{1f3fd}'));
+        assert_eq!(string.char_at(6), None);
+        let indices: Vec<_> = (0..8).map(|i| string.byte_index_of_char(i)).collect();
+        let expected = [0, 1, 5, 6, 10, 14, 15].map(Some);
+        assert_eq!(indices[..7], expected);
+        assert_eq!(indices[7], None);
+        let slice = string.slice(5..);
+        assert_eq!(slice.char_at(1), Some('\u{1f44d}'));
+        assert_eq!(slice.byte_index_of_char(2), Some(5));
     }
 
     #[test]
-    fn test_deref<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.deref(), string.as_str());
+    fn test_line<S: Data<String>>(string: ImString<S>) {
+        let lines: Vec<_> = string.as_str().lines().collect();
+        assert_eq!(string.line_count(), lines.len());
+        for (index, line) in lines.iter().enumerate() {
+            assert_eq!(string.line(index).unwrap(), *line);
+        }
+        assert_eq!(string.line(lines.len()), None);
     }
 
     #[test]
-    fn test_clone<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string, string.clone());
+    fn test_line_endings<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("one\r\ntwo\n\nfour\r\n");
+        assert_eq!(string.line_count(), 4);
+        assert_eq!(string.line(0).unwrap(), "one");
+        assert_eq!(string.line(1).unwrap(), "two");
+        assert_eq!(string.line(1).unwrap().offset, 5..8);
+        assert_eq!(string.line(2).unwrap(), "");
+        assert_eq!(string.line(3).unwrap(), "four");
+        assert_eq!(string.line(4), None);
+        let string = string.slice(..string.len() - 2);
+        assert_eq!(string.line_count(), 4);
+        assert_eq!(string.line(3).unwrap(), "four");
+        let empty: ImString<S> = ImString::new();
+        assert_eq!(empty.line_count(), 0);
+        assert_eq!(empty.line(0), None);
     }
 
     #[test]
-    fn test_display<S: Data<String>>(string: ImString<S>) {
-        let display_string = format!("{string}");
-        let display_str = format!("{}", string.as_str());
-        assert_eq!(display_string, display_str);
+    fn test_iterator_size_hint<S: Data<String>>(string: ImString<S>) {
+        let hints = [
+            string.split(" ").size_hint(),
+            string.split_whitespace().size_hint(),
+            string.matches("o").size_hint(),
+            string.lines_with_terminators().size_hint(),
+        ];
+        let std_hints = [
+            string.as_str().split(" ").size_hint(),
+            string.as_str().split_whitespace().size_hint(),
+            string.as_str().matches("o").size_hint(),
+            string.as_str().split_inclusive('\n').size_hint(),
+        ];
+        assert_eq!(hints, std_hints);
+        let words: Vec<&str> = string.as_str().split(' ').collect();
+        let iterator = ImStringIterator::new(&string.string, words.clone().into_iter());
+        assert_eq!(iterator.size_hint(), (words.len(), Some(words.len())));
+        assert_eq!(iterator.collect::<Vec<_>>(), words);
     }
 
     #[test]
-    fn test_insert_start<S: Data<String>>(string: ImString<S>) {
-        let mut string = string;
-        let length = string.len();
-        string.insert(0, 'h');
-        assert_eq!(string.len(), length + 1);
-        assert_eq!(string.chars().nth(0), Some('h'));
+    fn test_owned_iterators<S: Data<String>>(string: ImString<S>) {
+        assert!(string.chars_owned().eq(string.as_str().chars()));
+        assert!(string.chars_owned().rev().eq(string.as_str().chars().rev()));
+        assert!(string.char_indices_owned().eq(string.as_str().char_indices()));
+        let indices = string.char_indices_owned().rev();
+        assert!(indices.eq(string.as_str().char_indices().rev()));
+        assert!(string.bytes_owned().eq(string.as_str().bytes()));
+        assert!(string.bytes_owned().rev().eq(string.as_str().bytes().rev()));
+        assert_eq!(string.bytes_owned().len(), string.len());
     }
 
     #[test]
-    fn test_insert_one<S: Data<String>>(string: ImString<S>) {
-        if !string.is_empty() && string.is_char_boundary(1) {
-            let mut string = string;
-            let length = string.len();
-            string.insert(1, 'h');
-            assert_eq!(string.len(), length + 1);
-            assert_eq!(string.chars().nth(1), Some('h'));
+    fn test_owned_iterators_outlive<S: Data<String>>() {
+        fn create<S: Data<String>>() -> (IntoChars<S>, IntoCharIndices<S>, IntoBytes<S>) {
+            let string: ImString<S> = ImString::from("say h\u{e9}llo").slice(4..);
+            (
+                string.chars_owned(),
+                string.char_indices_owned(),
+                string.bytes_owned(),
+            )
         }
+        let (chars, mut indices, mut bytes) = create::<S>();
+        assert_eq!(chars.collect::<String>(), "h\u{e9}llo");
+        assert_eq!(indices.next(), Some((0, 'h')));
+        assert_eq!(indices.next_back(), Some((5, 'o')));
+        assert_eq!(indices.as_str(), "\u{e9}ll");
+        assert_eq!(indices.collect::<Vec<_>>(), [(1, '\u{e9}'), (3, 'l'), (4, 'l')]);
+        assert_eq!(bytes.next(), Some(b'h'));
+        assert_eq!(bytes.next_back(), Some(b'o'));
+        assert_eq!(bytes.as_bytes(), "\u{e9}ll".as_bytes());
+        assert_eq!(bytes.len(), 4);
     }
 
     #[test]
-    fn test_insert_end<S: Data<String>>(string: ImString<S>) {
-        let mut string = string;
-        let length = string.len();
-        string.insert(length, 'h');
-        assert_eq!(string.len(), length + 1);
-        // FIXME
-        //assert_eq!(string.chars().nth(length), Some('h'));
+    fn test_bytes_owned_partial_char<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{e9}\u{1f600}");
+        let mut bytes = string.bytes_owned();
+        assert_eq!(bytes.next(), Some(0xc3));
+        assert_eq!(bytes.next_back(), Some(0x80));
+        assert_eq!(bytes.as_bytes(), b"\xa9\xf0\x9f\x98");
+        assert_eq!(bytes.len(), 4);
+        assert_eq!(bytes.collect::<Vec<_>>(), b"\xa9\xf0\x9f\x98");
     }
 
     #[test]
-    fn test_is_empty<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.is_empty(), string.len() == 0);
+    fn test_shrink_offset_to_fit<S: Data<String>>(string: ImString<S>) {
+        let mut shrunk = string.clone();
+        shrunk.shrink_offset_to_fit();
+        assert_eq!(shrunk, string);
+        drop(string);
+        let capacity = shrunk.capacity();
+        let unique = shrunk.is_unique();
+        shrunk.shrink_offset_to_fit();
+        if unique {
+            assert_eq!(shrunk.raw_offset(), 0..shrunk.len());
+            assert_eq!(shrunk.string.get_borrowed().len(), shrunk.len());
+            assert_eq!(shrunk.capacity(), capacity);
+        }
     }
 
     #[test]
-    fn test_push<S: Data<String>>(string: ImString<S>) {
-        let mut string = string;
-        let mut std_string = string.as_str().to_string();
-        let c = 'c';
-        std_string.push(c);
-        string.push(c);
-        assert_eq!(string, std_string);
+    fn test_shrink_offset_to_fit_slice<S: Data<String>>() {
+        let document: ImString<S> = ImString::from("x".repeat(1024));
+        let mut string = document.slice(512..516);
+        drop(document);
+        let capacity = string.capacity();
+        string.shrink_offset_to_fit();
+        assert_eq!(string, "xxxx");
+        assert_eq!(string.raw_offset(), 0..4);
+        assert_eq!(string.capacity(), capacity);
+        assert_eq!(string.string.get_borrowed(), "xxxx");
+        let pointer = string.as_ptr();
+        string.push_str(&"y".repeat(512));
+        assert_eq!(string.as_ptr(), pointer);
+        assert_eq!(string.len(), 516);
     }
 
     #[test]
-    fn test_push_str<S: Data<String>>(string: ImString<S>) {
-        let mut string = string;
-        let mut std_string = string.as_str().to_string();
-        let s = "string";
-        std_string.push_str(s);
-        string.push_str(s);
-        assert_eq!(string, std_string);
+    fn test_replace_range<S: Data<String>>(string: ImString<S>) {
+        let boundaries: Vec<_> = (0..=string.len())
+            .filter(|index| string.is_char_boundary(*index))
+            .collect();
+        for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
+            for replacement in ["", "-", "\u{1f600}\u{1f600}"] {
+                let mut expected = string.as_str().to_string();
+                expected.replace_range(start..end, replacement);
+                let mut replaced = string.clone();
+                replaced.replace_range(start..end, replacement);
+                assert_eq!(replaced, expected);
+            }
+        }
     }
 
     #[test]
-    fn test_slice_all<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.slice(..), string);
+    fn test_replace_range_lengths<S: Data<String>>() {
+        let document: ImString<S> = ImString::from("say hello world");
+        let mut string = document.slice(4..);
+        string.replace_range(0..5, "hi");
+        assert_eq!(string, "hi world");
+        assert_eq!(string.offset.len(), 8);
+        string.replace_range(3.., "wonderful world");
+        assert_eq!(string, "hi wonderful world");
+        assert_eq!(string.offset.len(), 18);
+        string.replace_range(2..12, "");
+        assert_eq!(string, "hi world");
+        assert_eq!(string.offset.len(), 8);
+        string.replace_range(.., "");
+        assert_eq!(string, "");
+        assert!(string.offset.is_empty());
+        assert_eq!(document, "say hello world");
     }
 
     #[test]
-    fn test_slice_start<S: Data<String>>(string: ImString<S>) {
-        for end in 0..string.len() {
-            if string.is_char_boundary(end) {
-                assert_eq!(string.slice(..end), string.as_str()[..end]);
-            }
+    fn test_insert_str_end<S: Data<String>>(string: ImString<S>) {
+        let mut inserted = string.clone();
+        inserted.insert(inserted.len(), '\u{e9}');
+        inserted.insert_str(inserted.len(), "xy");
+        assert_eq!(inserted, format!("{string}\u{e9}xy"));
+        assert_eq!(string, string.as_str());
+    }
+
+    #[test]
+    fn test_insert_end_in_place<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::with_capacity(64);
+        string.push_str("start");
+        let capacity = string.capacity();
+        let pointer = string.as_ptr();
+        for c in "\u{e9}nd".chars() {
+            string.insert(string.len(), c);
+        }
+        for _ in 0..8 {
+            string.insert_str(string.len(), "-more");
         }
+        assert_eq!(string, format!("start\u{e9}nd{}", "-more".repeat(8)));
+        assert_eq!(string.capacity(), capacity);
+        assert_eq!(string.as_ptr(), pointer);
+        let mut tail = string.slice(5..);
+        drop(string);
+        // with cloned storage, the slice is a copy which has no spare capacity
+        tail.reserve(1);
+        let pointer = tail.as_ptr();
+        tail.insert_str(tail.len(), "!");
+        assert_eq!(tail, format!("\u{e9}nd{}!", "-more".repeat(8)));
+        assert_eq!(tail.as_ptr(), pointer);
     }
 
     #[test]
-    fn test_slice_end<S: Data<String>>(string: ImString<S>) {
-        for start in 0..string.len() {
-            if string.is_char_boundary(start) {
-                assert_eq!(string.slice(start..), string.as_str()[start..]);
+    fn test_try_truncate<S: Data<String>>(string: ImString<S>) {
+        for length in 0..string.len() + 2 {
+            let mut truncated = string.clone();
+            let mut expected = string.as_str().to_string();
+            if string.as_str().is_char_boundary(length) || length > string.len() {
+                truncated.try_truncate(length).unwrap();
+                expected.truncate(length);
+                assert_eq!(truncated, expected);
+            } else {
+                assert_eq!(truncated.try_truncate(length), Err(SliceError::EndNotAligned));
+                assert_eq!(truncated, string);
             }
         }
     }
 
     #[test]
-    fn test_slice_middle<S: Data<String>>(string: ImString<S>) {
-        for start in 0..string.len() {
-            if string.is_char_boundary(start) {
-                for end in start..string.len() {
-                    if string.is_char_boundary(end) {
-                        assert_eq!(string.slice(start..end), string.as_str()[start..end]);
-                    }
+    fn test_truncate_multibyte<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("a\u{fc}\u{1f600}");
+        assert_eq!(string.try_truncate(2), Err(SliceError::EndNotAligned));
+        assert_eq!(string.try_truncate(4), Err(SliceError::EndNotAligned));
+        assert_eq!(string, "a\u{fc}\u{1f600}");
+        string.truncate(3);
+        assert_eq!(string, "a\u{fc}");
+        let mut slice = string.slice(1..);
+        assert_eq!(slice.try_truncate(1), Err(SliceError::EndNotAligned));
+        slice.truncate(0);
+        assert_eq!(slice, "");
+        assert_eq!(slice.offset, 1..1);
+    }
+
+    #[test]
+    fn test_rsplit<S: Data<String>>(string: ImString<S>) {
+        for pattern in ["", " ", "\n", "o", "\u{fc}", "ng"] {
+            let mut parts = string.rsplit(pattern);
+            let mut std_parts = string.as_str().rsplit(pattern);
+            loop {
+                let (part, std_part) = (parts.next(), std_parts.next());
+                assert_eq!(part.as_ref().map(ImString::as_str), std_part);
+                if part.is_none() {
+                    break;
                 }
             }
+            let mut parts: Vec<_> = string.rsplit(pattern).collect();
+            parts.reverse();
+            let std_parts: Vec<_> = string.as_str().split(pattern).collect();
+            assert_eq!(parts, std_parts);
+        }
+    }
+
+    #[test]
+    fn test_rsplit_extension<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("dir.d/file.tar.gz");
+        let extension = string.rsplit(".").next().unwrap();
+        assert_eq!(extension, "gz");
+        assert_eq!(extension.offset, 15..17);
+        let parts: Vec<_> = string.rsplit("/").collect();
+        assert_eq!(parts, ["file.tar.gz", "dir.d"]);
+        let empty: ImString<S> = ImString::from(".");
+        assert_eq!(empty.rsplit(".").collect::<Vec<_>>(), ["", ""]);
+    }
+
+    #[test]
+    fn test_contains_imstr<S: Data<String>>(string: ImString<S>) {
+        for length in (0..=string.len()).filter(|i| string.is_char_boundary(*i)) {
+            let prefix: ImString<Local> = string.slice(..length).clone_as();
+            let suffix: ImString<Cloned<String>> = string.slice(length..).clone_as();
+            assert!(string.starts_with_imstr(&prefix));
+            assert!(string.ends_with_imstr(&suffix));
+            assert!(string.contains_imstr(&prefix));
+            assert!(string.contains_imstr(&suffix));
+            assert_eq!(
+                string.starts_with_imstr(&suffix),
+                string.as_str().starts_with(suffix.as_str())
+            );
+            assert_eq!(
+                string.ends_with_imstr(&prefix),
+                string.as_str().ends_with(prefix.as_str())
+            );
         }
     }
 
     #[test]
-    fn test_try_slice_all<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.try_slice(..).unwrap(), string);
+    fn test_contains_imstr_storages<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("key: value");
+        let key: ImString<Threadsafe> = ImString::from("my key").slice(3..);
+        let value: ImString<Box<String>> = ImString::from("value");
+        let missing: ImString<Local> = ImString::from("Value");
+        assert!(string.starts_with_imstr(&key));
+        assert!(!string.ends_with_imstr(&key));
+        assert!(string.ends_with_imstr(&value));
+        assert!(string.contains_imstr(&string.slice(3..5)));
+        assert!(!string.contains_imstr(&missing));
+        assert!(!string.ends_with_imstr(&missing));
+        assert!(key.starts_with_imstr(&ImString::<S>::new()));
     }
 
     #[test]
-    fn test_try_slice_start<S: Data<String>>(string: ImString<S>) {
-        for end in 0..string.len() {
-            if string.is_char_boundary(end) {
-                assert_eq!(string.try_slice(..end).unwrap(), string.as_str()[..end]);
-            } else {
-                // cannot get slice with end in middle of UTF-8 multibyte sequence.
-                assert_eq!(string.try_slice(..end), Err(SliceError::EndNotAligned));
+    fn test_split_off_back<S: Data<String>>(string: ImString<S>) {
+        for position in 0..=string.len() + 1 {
+            let mut left = string.clone();
+            let split = string.len().checked_sub(position);
+            match split.and_then(|split| string.as_str().get(split..)) {
+                Some(expected) => {
+                    let right = left.split_off_back(position);
+                    assert_eq!(right, expected);
+                    assert_eq!(left, string.as_str()[..string.len() - position]);
+                    assert_eq!(left.offset.end, right.offset.start);
+                }
+                None => {
+                    assert_eq!(left.try_split_off_back(position), None);
+                    assert_eq!(left, string);
+                }
             }
         }
-
-        // cannot get slice with end pointing past the end of the string.
-        assert_eq!(string.try_slice(..string.len()+1), Err(SliceError::EndOutOfBounds));
     }
 
     #[test]
-    fn test_try_slice_end<S: Data<String>>(string: ImString<S>) {
-        for start in 0..string.len() {
-            if string.is_char_boundary(start) {
-                assert_eq!(string.try_slice(start..).unwrap(), string.as_str()[start..]);
-            } else {
-                // cannot get slice with end in middle of UTF-8 multibyte sequence.
-                assert_eq!(string.try_slice(start..), Err(SliceError::StartNotAligned));
-            }
+    fn test_split_off_back_chars<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::from("a\u{e9}\u{20ac}\u{1f600}");
+        assert_eq!(string.try_split_off_back(1), None);
+        assert_eq!(string.try_split_off_back(11), None);
+        let mut chars = Vec::new();
+        while let Some(c) = string.chars().next_back() {
+            chars.push(string.split_off_back(c.len_utf8()));
         }
+        assert_eq!(chars, ["\u{1f600}", "\u{20ac}", "\u{e9}", "a"]);
+        assert_eq!(chars[1].offset, 3..6);
+        assert_eq!(string, "");
+        assert_eq!(string.offset, 0..0);
+    }
 
-        // cannot get slice with end pointing past the end of the string.
-        assert_eq!(string.try_slice(string.len()+1..), Err(SliceError::StartOutOfBounds));
+    #[test]
+    fn test_ensure_unique_capacity<S: Data<String>>(string: ImString<S>) {
+        let mut builder = string.clone();
+        builder.ensure_unique_capacity(64);
+        assert_eq!(builder, string);
+        assert!(builder.is_unique());
+        assert_eq!(builder.offset.start, 0);
+        assert!(builder.capacity() >= builder.len() + 64);
+        let pointer = builder.as_ptr();
+        for _ in 0..16 {
+            builder.push_str("ab");
+            builder.push('c');
+            builder.insert(builder.len(), 'd');
+        }
+        assert_eq!(builder.as_ptr(), pointer);
+        assert_eq!(builder, format!("{string}{}", "abcd".repeat(16)));
     }
 
     #[test]
-    fn test_add_assign<S: Data<String>>(string: ImString<S>) {
-        let mut std_string = string.as_str().to_string();
-        let mut string = string;
-        string += "hello";
-        std_string += "hello";
-        assert_eq!(string, std_string);
+    fn test_ensure_unique_capacity_shared<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::with_capacity(32);
+        string.push_str("hello");
+        let clone = string.clone();
+        let slice = string.slice(1..);
+        string.ensure_unique_capacity(16);
+        assert!(string.capacity() >= 21);
+        let pointer = string.as_ptr();
+        string.push_str(" world, again");
+        assert_eq!(string.as_ptr(), pointer);
+        assert_eq!(string, "hello world, again");
+        assert_eq!(clone, "hello");
+        assert_eq!(slice, "ello");
     }
 
     #[test]
-    fn test_add<S: Data<String>>(string: ImString<S>) {
-        let mut std_string = string.as_str().to_string();
-        let std_string = std_string + "hello";
-        let string = string + "hello";
-        assert_eq!(string, std_string);
+    fn test_ensure_unique_capacity_spare<S: Data<String>>() {
+        let mut string: ImString<S> = ImString::with_capacity(32);
+        string.push_str("hello");
+        let capacity = string.capacity();
+        let pointer = string.as_ptr();
+        string.ensure_unique_capacity(8);
+        assert_eq!(string.capacity(), capacity);
+        assert_eq!(string.as_ptr(), pointer);
+        string.ensure_unique_capacity(27);
+        assert_eq!(string.capacity(), capacity);
+        string.ensure_unique_capacity(28);
+        assert!(string.capacity() >= 33);
+        assert_eq!(string, "hello");
     }
 
     #[test]
-    fn test_to_socket_addrs<S: Data<String>>(string: ImString<S>) {
-        #[cfg(not(miri))]
-        {
-            let addrs = string.to_socket_addrs().map(|s| s.collect::<Vec<_>>());
-            let str_addrs = string.as_str().to_socket_addrs().map(|s| s.collect::<Vec<_>>());
-            match addrs {
-                Ok(addrs) => assert_eq!(addrs, str_addrs.unwrap()),
-                Err(err) => assert!(str_addrs.is_err()),
-            }
+    fn test_display_flags<S: Data<String>>(string: ImString<S>) {
+        let s = string.as_str();
+        assert_eq!(format!("{string:>12}"), format!("{s:>12}"));
+        assert_eq!(format!("{string:<12}"), format!("{s:<12}"));
+        assert_eq!(format!("{string:^12}"), format!("{s:^12}"));
+        assert_eq!(format!("{string:*^13}"), format!("{s:*^13}"));
+        assert_eq!(format!("{string:\u{e4}>9}"), format!("{s:\u{e4}>9}"));
+        for precision in 0..5 {
+            assert_eq!(format!("{string:.precision$}"), format!("{s:.precision$}"));
+            assert_eq!(
+                format!("{string:-^8.precision$}"),
+                format!("{s:-^8.precision$}")
+            );
         }
     }
 
     #[test]
-    fn test_from_iterator_char<S: Data<String>>() {
-        let input = ['h', 'e', 'l', 'l', 'o'];
-        let string: ImString<S> = input.into_iter().collect();
-        assert_eq!(string, "hello");
+    fn test_display_precision_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("h\u{e9}ll\u{f6} w\u{f6}rld");
+        assert_eq!(format!("{string:.2}"), "h\u{e9}");
+        assert_eq!(format!("{string:.5}"), "h\u{e9}ll\u{f6}");
+        assert_eq!(format!("{string:>7.3}"), "    h\u{e9}l");
+        let slice = string.slice(1..);
+        assert_eq!(format!("{slice:.3}"), "\u{e9}ll");
+        assert_eq!(format!("{slice:_<6.4}"), "\u{e9}ll\u{f6}__");
     }
 
     #[test]
-    fn test_from_iterator_char_ref<S: Data<String>>() {
-        let input = ['h', 'e', 'l', 'l', 'o'];
-        let string: ImString<S> = input.iter().collect();
-        assert_eq!(string, "hello");
+    fn test_debug_flags<S: Data<String>>(string: ImString<S>) {
+        let s = string.as_str();
+        assert_eq!(format!("{string:#?}"), format!("{s:#?}"));
+        assert_eq!(format!("{string:>20?}"), format!("{s:>20?}"));
+        assert_eq!(format!("{string:-<20?}"), format!("{s:-<20?}"));
     }
 
     #[test]
-    fn test_from_iterator_str<S: Data<String>>() {
-        let input = ["hello", "world", "!"];
-        let string: ImString<S> = input.into_iter().collect();
-        assert_eq!(string, "helloworld!");
+    fn test_debug_escape<S: Data<String>>() {
+        let s = "say \"hi\"\n\tand 'bye'\\\u{0}\u{e9}\u{200b}";
+        let string: ImString<S> = ImString::from(s);
+        assert_eq!(format!("{string:?}"), format!("{s:?}"));
+        assert_eq!(format!("{string:#?}"), format!("{s:#?}"));
+        let slice = string.slice(4..9);
+        assert_eq!(format!("{slice:?}"), r#""\"hi\"\n""#);
     }
 
     #[test]
-    fn test_extend_char<S: Data<String>>() {
-        let input = ['h', 'e', 'l', 'l', 'o'];
-        let mut string: ImString<S> = ImString::new();
-        string.extend(input.into_iter());
-        assert_eq!(string, "hello");
+    fn test_debug_derived<S: Data<String>>() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Token<T> {
+            name: T,
+            value: T,
+        }
+
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Expected<'a> {
+            name: &'a str,
+            value: &'a str,
+        }
+
+        let input: ImString<S> = ImString::from("key=\"va\nlue\"");
+        let token: Token<ImString<S>> = Token {
+            name: input.slice(..3),
+            value: input.slice(4..),
+        };
+        let expected = Expected {
+            name: "key",
+            value: "\"va\nlue\"",
+        };
+        assert_eq!(format!("{token:?}"), format!("{expected:?}").replace("Expected", "Token"));
+        assert_eq!(format!("{token:#?}"), format!("{expected:#?}").replace("Expected", "Token"));
+        assert_eq!(
+            format!("{token:#?}"),
+            "Token {\n    name: \"key\",\n    value: \"\\\"va\\nlue\\\"\",\n}"
+        );
     }
 
     #[test]
-    fn test_extend_char_ref<S: Data<String>>() {
-        let input = ['h', 'e', 'l', 'l', 'o'];
-        let mut string: ImString<S> = ImString::new();
-        string.extend(input.into_iter());
-        assert_eq!(string, "hello");
+    fn test_extend_from_within<S: Data<String>>(string: ImString<S>) {
+        let mut doubled = string.clone();
+        doubled.extend_from_within(..);
+        assert_eq!(doubled, format!("{string}{string}"));
+        assert_eq!(string, string.as_str());
+
+        let prefix: String = string.chars().take(3).collect();
+        let mut extended = string.clone();
+        extended.extend_from_within(..prefix.len());
+        assert_eq!(extended, format!("{string}{prefix}"));
     }
 
     #[test]
-    fn test_extend_str<S: Data<String>>() {
-        let input = ["hello", "world", "!"];
-        let mut string: ImString<S> = ImString::new();
-        string.extend(input.into_iter());
-        assert_eq!(string, "helloworld!");
+    fn test_extend_from_within_slice<S: Data<String>>() {
+        let document: ImString<S> = ImString::from("say h\u{e9}llo");
+        let mut string = document.slice(4..);
+        string.extend_from_within(1..=2);
+        assert_eq!(string, "h\u{e9}llo\u{e9}");
+        string.extend_from_within(3..5);
+        assert_eq!(string, "h\u{e9}llo\u{e9}ll");
+        assert_eq!(document, "say h\u{e9}llo");
     }
 
     #[test]
-    fn test_from_utf8_lossy<S: Data<String>>() {
-        let string: ImString<S> = ImString::from_utf8_lossy(b"hello");
-        assert_eq!(string, "hello");
+    fn test_count_matches<S: Data<String>>(string: ImString<S>) {
+        for pattern in ["", "l", "o", "ll", "\u{e4}", "missing"] {
+            assert_eq!(
+                string.count_matches(pattern),
+                string.as_str().matches(pattern).count()
+            );
+            assert_eq!(
+                string.count_matches(pattern),
+                string.matches(pattern).count()
+            );
+        }
     }
 
     #[test]
-    fn test_from_utf8_unchecked<S: Data<String>>() {
-        let string: ImString<S> = unsafe {
-            ImString::from_utf8_unchecked(b"hello".to_vec())
-        };
-        assert_eq!(string, "hello");
+    fn test_count_matches_overlapping<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("aaaa\u{e9}aa");
+        assert_eq!(string.count_matches("aa"), 3);
+        assert_eq!(string.count_matches("aaa"), 1);
+        assert_eq!(string.count_matches("a\u{e9}a"), 1);
+        assert_eq!(string.count_matches("b"), 0);
+        assert_eq!(string.count_matches(""), 8);
+        assert_eq!(string.slice(1..4).count_matches("aa"), 1);
     }
 
     #[test]
-    fn test_as_ref_str<S: Data<String>>(string: ImString<S>) {
-        let s: &str = string.as_ref();
-        assert_eq!(s, string.as_str());
+    fn test_is_ascii<S: Data<String>>(string: ImString<S>) {
+        assert_eq!(string.is_ascii(), string.as_str().is_ascii());
     }
 
     #[test]
-    fn test_as_ref_bytes<S: Data<String>>(string: ImString<S>) {
-        let s: &[u8] = string.as_ref();
-        assert_eq!(s, string.as_bytes());
+    fn test_to_ascii_case_unchanged<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("Content-Type: text_html");
+        let lower = string.slice(14..);
+        assert!(lower.is_ascii());
+        assert_eq!(lower.to_ascii_lowercase(), "text_html");
+        assert_eq!(lower.to_ascii_uppercase(), "TEXT_HTML");
+        let mixed = string.slice(..12);
+        assert_eq!(mixed.to_ascii_lowercase(), "content-type");
+        assert_eq!(mixed.to_ascii_uppercase(), "CONTENT-TYPE");
+        assert_eq!(lower.ascii_lowercase(), "text_html");
+        assert_eq!(lower.ascii_uppercase(), "TEXT_HTML");
+        assert_eq!(mixed.ascii_lowercase(), "content-type");
+        assert_eq!(mixed.ascii_uppercase(), "CONTENT-TYPE");
     }
 
     #[test]
-    fn test_as_ref_path<S: Data<String>>(string: ImString<S>) {
-        let s: &Path = string.as_ref();
-        assert_eq!(s, string.as_str().as_ref() as &Path);
+    fn test_path_round_trip<S: Data<String>>(string: ImString<S>) {
+        let path = string.to_path_buf();
+        assert_eq!(path.as_os_str(), string.as_str());
+        assert_eq!(ImString::<S>::try_from(path.as_path()).unwrap(), string);
+        assert_eq!(ImString::<S>::try_from(path.clone()).unwrap(), string);
+        assert_eq!(PathBuf::from(string.clone()), path);
     }
 
     #[test]
-    fn test_as_ref_os_str<S: Data<String>>(string: ImString<S>) {
-        let s: &OsStr = string.as_ref();
-        assert_eq!(s, string.as_str().as_ref() as &OsStr);
+    fn test_try_from_path_buf<S: Data<String>>() {
+        let path = PathBuf::from("/home/f\u{e9}rris/src");
+        let pointer = path.as_os_str().as_encoded_bytes().as_ptr();
+        let string = ImString::<S>::try_from(path).unwrap();
+        assert_eq!(string, "/home/f\u{e9}rris/src");
+        // strings which fit inline are copied instead
+        if string.heap_size() > 0 {
+            assert_eq!(string.as_ptr(), pointer);
+        }
+        let path = PathBuf::from(string.slice(6..));
+        assert_eq!(path.file_name().unwrap(), "src");
+        assert_eq!(path.parent().unwrap(), Path::new("f\u{e9}rris"));
     }
 
     #[test]
-    fn test_partial_eq<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string, string.as_str());
-        assert_eq!(string, string.to_string());
-        assert_eq!(string, string);
+    fn test_chunks_bytes<S: Data<String>>(string: ImString<S>) {
+        for size in 1..8 {
+            let chunks: Vec<ImString<S>> = string.chunks_bytes(size).collect();
+            assert_eq!(ImString::concat(&chunks), string);
+            for chunk in &chunks {
+                assert!(!chunk.is_empty());
+                assert!(chunk.len() <= size || chunk.chars().count() == 1);
+            }
+            let (lower, upper) = string.chunks_bytes(size).size_hint();
+            assert!(lower <= chunks.len());
+            assert!(chunks.len() <= upper.unwrap());
+        }
     }
 
     #[test]
-    fn test_from<S: Data<String>>(string: ImString<S>) {
-        let std_string: String = string.clone().into();
-        assert_eq!(string, std_string);
+    fn test_chunks_chars<S: Data<String>>(string: ImString<S>) {
+        for size in 1..8 {
+            let chunks: Vec<ImString<S>> = string.chunks_chars(size).collect();
+            assert_eq!(ImString::concat(&chunks), string);
+            let expected: Vec<String> = string
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(size)
+                .map(|chars| chars.iter().collect())
+                .collect();
+            assert_eq!(chunks, expected);
+            let (lower, upper) = string.chunks_chars(size).size_hint();
+            assert!(lower <= chunks.len());
+            assert!(chunks.len() <= upper.unwrap());
+        }
     }
 
     #[test]
-    fn test_raw_offset<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.offset, string.raw_offset());
+    fn test_chunks_bytes_emoji<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("abc\u{1f600}de\u{1f603}");
+        let chunks: Vec<ImString<S>> = string.chunks_bytes(4).collect();
+        assert_eq!(chunks, ["abc", "\u{1f600}", "de", "\u{1f603}"]);
+        let chunks: Vec<ImString<S>> = string.chunks_bytes(5).collect();
+        assert_eq!(chunks, ["abc", "\u{1f600}d", "e\u{1f603}"]);
+        let chunks: Vec<ImString<S>> = string.chunks_bytes(2).collect();
+        assert_eq!(chunks, ["ab", "c", "\u{1f600}", "de", "\u{1f603}"]);
+        let chunks: Vec<ImString<S>> = string.chunks_chars(4).collect();
+        assert_eq!(chunks, ["abc\u{1f600}", "de\u{1f603}"]);
+        let mut chunks = string.slice(3..).chunks_bytes(1);
+        assert_eq!(chunks.next().unwrap(), "\u{1f600}");
+        assert_eq!(chunks.as_str(), "de\u{1f603}");
+        assert_eq!(chunks.len(), 3);
+        let mut chunks = string.chunks_chars(3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.next().unwrap(), "abc");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.next().unwrap(), "\u{1f600}de");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks.next().unwrap(), "\u{1f603}");
+        assert_eq!(chunks.len(), 0);
     }
 
     #[test]
-    fn test_raw_string<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string.string.get(), string.raw_string().get());
+    fn test_chunks_chars_huge<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("h\u{e9}llo");
+        let mut chunks = string.chunks_chars(usize::MAX);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks.size_hint(), (1, Some(1)));
+        assert_eq!(chunks.next().unwrap(), string);
+        assert_eq!(chunks.len(), 0);
+        assert_eq!(chunks.next(), None);
+        let chunks: Vec<ImString<S>> = string.chunks_chars(usize::MAX).collect();
+        assert_eq!(chunks, [string]);
     }
 
     #[test]
-    fn into_std_string<S: Data<String>>(string: ImString<S>) {
-        let std_clone = string.as_str().to_string();
-        let std_string = string.into_std_string();
-        assert_eq!(std_clone, std_string);
+    fn test_rotate<S: Data<String>>(string: ImString<S>) {
+        for mid in 0..=string.len() + 1 {
+            if mid <= string.len() && string.is_char_boundary(mid) {
+                let expected = format!("{}{}", &string[mid..], &string[..mid]);
+                assert_eq!(string.rotate(mid), expected);
+                assert_eq!(string.try_rotate(mid).unwrap(), expected);
+            } else {
+                assert_eq!(string.try_rotate(mid), None);
+            }
+        }
+        assert_eq!(string.rotate(0), string);
+        assert_eq!(string.rotate(string.len()), string);
     }
 
     #[test]
-    fn test_truncate<S: Data<String>>(string: ImString<S>) {
-        let mut clone = string.as_str().to_string();
-        let mut string = string;
+    fn test_rotate_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("\u{1f600}ab\u{e9}");
+        assert_eq!(string.rotate(4), "ab\u{e9}\u{1f600}");
+        assert_eq!(string.rotate(6), "\u{e9}\u{1f600}ab");
+        assert_eq!(string.try_rotate(2), None);
+        assert_eq!(string.try_rotate(7), None);
+        let slice = string.slice(4..);
+        assert_eq!(slice.rotate(1), "b\u{e9}a");
+        assert_eq!(slice.rotate(1).rotate(3), "ab\u{e9}");
+    }
 
-        for length in (0..string.len()).rev() {
-            if string.is_char_boundary(length) {
-                string.truncate(length);
-                clone.truncate(length);
-                assert_eq!(string, clone);
+    #[test]
+    fn test_split_first_char<S: Data<String>>(string: ImString<S>) {
+        match string.split_first_char() {
+            Some((c, rest)) => {
+                assert_eq!(Some(c), string.chars().next());
+                assert_eq!(rest, &string[c.len_utf8()..]);
             }
+            None => assert!(string.is_empty()),
         }
     }
 
     #[test]
-    fn test_str_ref<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string, string.str_ref(string.as_str()));
+    fn test_split_last_char<S: Data<String>>(string: ImString<S>) {
+        match string.split_last_char() {
+            Some((c, rest)) => {
+                assert_eq!(Some(c), string.chars().next_back());
+                assert_eq!(rest, &string[..string.len() - c.len_utf8()]);
+            }
+            None => assert!(string.is_empty()),
+        }
     }
 
     #[test]
-    fn test_try_str_ref<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string, string.try_str_ref(string.as_str()).unwrap());
-        assert_eq!(string.try_str_ref("test"), None);
+    fn test_split_first_last_char_multibyte<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("say \u{1f600}x\u{e9}\u{1f603}");
+        let word = string.slice(4..);
+        let (first, rest) = word.split_first_char().unwrap();
+        assert_eq!(first, '\u{1f600}');
+        assert_eq!(rest, "x\u{e9}\u{1f603}");
+        let (last, rest) = rest.split_last_char().unwrap();
+        assert_eq!(last, '\u{1f603}');
+        assert_eq!(rest, "x\u{e9}");
+        let (last, rest) = rest.split_last_char().unwrap();
+        assert_eq!(last, '\u{e9}');
+        let (first, rest) = rest.split_first_char().unwrap();
+        assert_eq!(first, 'x');
+        assert_eq!(rest, "");
+        assert_eq!(rest.split_first_char(), None);
+        assert_eq!(rest.split_last_char(), None);
     }
+}
 
-    #[test]
-    fn test_slice_ref<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string, string.slice_ref(string.as_bytes()));
+#[test]
+fn test_cloned_parity() {
+    fn check(cloned: &ImString<Cloned<String>>, shared: &ImString<Threadsafe>) {
+        assert_eq!(cloned, shared);
+        assert_eq!(cloned.len(), shared.len());
+        assert!(cloned.capacity() >= cloned.len());
+        assert!(shared.capacity() >= shared.len());
     }
 
-    #[test]
-    fn test_try_slice_ref<S: Data<String>>(string: ImString<S>) {
-        assert_eq!(string, string.try_slice_ref(string.as_bytes()).unwrap());
-        assert_eq!(string.try_slice_ref(b"test"), None);
+    let strings = test_strings::<Cloned<String>>()
+        .into_iter()
+        .zip(test_strings::<Threadsafe>());
+    for (mut cloned, mut shared) in strings {
+        check(&cloned, &shared);
+
+        cloned.push_str("push");
+        shared.push_str("push");
+        check(&cloned, &shared);
+
+        cloned.insert(0, 'i');
+        shared.insert(0, 'i');
+        check(&cloned, &shared);
+
+        cloned.insert_str(1, "insert");
+        shared.insert_str(1, "insert");
+        check(&cloned, &shared);
+
+        cloned.truncate(4);
+        shared.truncate(4);
+        check(&cloned, &shared);
+
+        cloned.clear();
+        shared.clear();
+        check(&cloned, &shared);
+    }
+}
+
+#[test]
+fn test_capitalize_first_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello World");
+    let world = string.slice(6..);
+    let capitalized = world.capitalize_first();
+    assert_eq!(capitalized, "World");
+    assert!(Arc::ptr_eq(&capitalized.string, &string.string));
+    assert_eq!(capitalized.offset, world.offset);
+
+    let capitalized = string.capitalize_first();
+    assert_eq!(capitalized, "Hello World");
+    assert!(!Arc::ptr_eq(&capitalized.string, &string.string));
+}
+
+#[test]
+fn test_lines_next_back_shared() {
+    let string: ImString<Threadsafe> = ImString::from("first\nsecond\nlast");
+    let last = string.lines().next_back().unwrap();
+    assert_eq!(last, "last");
+    assert_eq!(last.offset, 13..17);
+    assert!(Arc::ptr_eq(&last.string, &string.string));
+}
+
+#[test]
+fn test_translate_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello");
+    let translated = string.translate("xyz", "abc");
+    assert_eq!(translated, "hello");
+    assert!(Arc::ptr_eq(&translated.string, &string.string));
+}
+
+#[test]
+#[should_panic]
+fn test_translate_mismatched() {
+    let string: ImString<Threadsafe> = ImString::from("hello");
+    string.translate("abc", "xy");
+}
+
+#[test]
+fn test_trim_shared() {
+    let string: ImString<Threadsafe> = ImString::from("  hello  ");
+    let trimmed = string.trim();
+    assert_eq!(trimmed.offset, 2..7);
+    assert!(Arc::ptr_eq(&trimmed.string, &string.string));
+}
+
+#[test]
+fn test_shared_heap_size() {
+    let string: ImString<Threadsafe> = ImString::with_capacity(300);
+    let first = string.clone();
+    let second = string.slice(..);
+    assert_eq!(string.heap_size(), 300);
+    assert_eq!(string.shared_heap_size(), 100);
+    assert_eq!(first.shared_heap_size(), 100);
+    drop(second);
+    assert_eq!(first.shared_heap_size(), 150);
+
+    let string: ImString<Local> = ImString::with_capacity(300);
+    let _clones = [string.clone(), string.clone()];
+    assert_eq!(string.shared_heap_size(), 100);
+}
+
+#[test]
+fn test_split_shared() {
+    let string: ImString<Threadsafe> = ImString::from("key=value");
+    let parts: Vec<_> = string.split("=").collect();
+    assert_eq!(parts[1].offset, 4..9);
+    assert!(parts
+        .iter()
+        .all(|part| Arc::ptr_eq(&part.string, &string.string)));
+}
+
+#[test]
+fn test_replace_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello world");
+    let world = string.slice(6..);
+    let replaced = world.replace("hello", "goodbye");
+    assert_eq!(replaced.offset, world.offset);
+    assert!(Arc::ptr_eq(&replaced.string, &string.string));
+    let replaced = world.replacen("o", "0", 0);
+    assert!(Arc::ptr_eq(&replaced.string, &string.string));
+}
+
+#[test]
+fn test_to_ascii_case_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello WORLD");
+    let hello = string.slice(..5);
+    assert!(Arc::ptr_eq(
+        &hello.to_ascii_lowercase().string,
+        &string.string
+    ));
+    assert!(!Arc::ptr_eq(
+        &hello.to_ascii_uppercase().string,
+        &string.string
+    ));
+    let world = string.slice(6..);
+    assert!(Arc::ptr_eq(
+        &world.to_ascii_uppercase().string,
+        &string.string
+    ));
+    assert!(!Arc::ptr_eq(
+        &world.to_ascii_lowercase().string,
+        &string.string
+    ));
+}
+
+#[test]
+fn test_make_ascii_case_in_place() {
+    let mut string: ImString<Threadsafe> = ImString::from("hello world");
+    let pointer = string.as_ptr();
+    string.make_ascii_uppercase();
+    assert_eq!(string, "HELLO WORLD");
+    assert_eq!(string.as_ptr(), pointer);
+
+    let clone = string.slice(6..);
+    string.make_ascii_lowercase();
+    assert_eq!(string, "hello world");
+    assert_eq!(clone, "WORLD");
+    assert_ne!(string.as_ptr(), pointer);
+    assert!(!Arc::ptr_eq(&string.string, &clone.string));
+}
+
+#[test]
+fn test_shrink_to_fit_shared() {
+    let document: ImString<Threadsafe> = ImString::from("x".repeat(1024));
+    let mut string = document.slice(512..516);
+    string.shrink_to_fit();
+    assert!(!Arc::ptr_eq(&document.string, &string.string));
+    assert_eq!(string, "xxxx");
+    assert!(string.capacity() < 1024);
+    assert_eq!(document.len(), 1024);
+}
+
+#[test]
+fn test_compact_shared() {
+    let document: ImString<Threadsafe> = ImString::from("hello, world!");
+    let string = document.slice(7..12);
+    let compact = string.compact();
+    assert!(Arc::ptr_eq(&document.string, &string.string));
+    assert!(!Arc::ptr_eq(&document.string, &compact.string));
+    assert_eq!(Arc::strong_count(&compact.string), 1);
+}
+
+#[test]
+fn test_ref_count_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello world");
+    let clone = string.clone();
+    assert_eq!(string.ref_count(), 2);
+    let slice = string.slice(6..);
+    assert_eq!(string.ref_count(), 3);
+    assert!(!slice.is_unique());
+    drop(clone);
+    assert_eq!(string.ref_count(), 2);
+    drop(slice);
+    assert_eq!(string.ref_count(), 1);
+    assert!(string.is_unique());
+
+    let string: ImString<Local> = ImString::from("hello world");
+    let clone = string.clone();
+    assert_eq!(clone.ref_count(), 2);
+    drop(string);
+    assert!(clone.is_unique());
+}
+
+#[test]
+fn test_into_bytes_reuse() {
+    let string: ImString<Threadsafe> = ImString::from("hello world");
+    let pointer = string.as_ptr();
+    let slice = string.slice(6..);
+    assert_eq!(slice.into_bytes(), b"world");
+    let hello = string.slice(..5);
+    assert_eq!(hello.clone().into_bytes(), b"hello");
+    drop(string);
+    let bytes = hello.into_bytes();
+    assert_eq!(bytes, b"hello");
+    assert_eq!(bytes.as_ptr(), pointer);
+}
+
+#[test]
+fn test_split_once_shared() {
+    let string: ImString<Threadsafe> = ImString::from("key=value");
+    let (key, value) = string.split_once("=").unwrap();
+    assert!(Arc::ptr_eq(&key.string, &string.string));
+    assert!(Arc::ptr_eq(&value.string, &string.string));
+    let (key, value) = string.rsplit_once("=").unwrap();
+    assert!(Arc::ptr_eq(&key.string, &string.string));
+    assert!(Arc::ptr_eq(&value.string, &string.string));
+}
+
+#[test]
+fn test_char_slices_shared() {
+    let string: ImString<Threadsafe> = ImString::from("h\u{e9}llo");
+    for (_, slice) in string.char_slices() {
+        assert!(Arc::ptr_eq(&slice.string, &string.string));
+    }
+}
+
+#[test]
+fn test_default_storage() {
+    let string: ImString = ImString::from("hello");
+    let storage: &Threadsafe = &string.string;
+    assert_eq!(storage.as_str(), "hello");
+}
+
+#[test]
+fn test_repeat_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello");
+    assert!(Arc::ptr_eq(&string.repeat(1).string, &string.string));
+    assert!(!Arc::ptr_eq(&string.repeat(2).string, &string.string));
+}
+
+#[test]
+fn test_append_in_place() {
+    let mut string: ImString<Threadsafe> = ImString::with_capacity(32);
+    string.push_str("hello");
+    let pointer = string.as_ptr();
+    let other: ImString<Threadsafe> = ImString::from("big world");
+    string.append(&other.slice(3..));
+    assert_eq!(string, "hello world");
+    assert_eq!(string.as_ptr(), pointer);
+}
+
+#[test]
+fn test_from_arc() {
+    let shared = Arc::new(String::from("hello world"));
+    let first = ImString::from_arc(shared.clone());
+    let second = ImString::from_arc(shared.clone());
+    assert_eq!(first.offset, 0..11);
+    assert_eq!(first.ref_count(), 3);
+    assert!(Arc::ptr_eq(&first.string, &second.string));
+    drop(shared);
+    assert_eq!(second.ref_count(), 2);
+}
+
+#[test]
+fn test_from_rc() {
+    let shared = Rc::new(String::from("h\u{e9}llo"));
+    let first = ImString::from_rc(shared.clone());
+    let second = ImString::from_rc(shared);
+    assert_eq!(first, "h\u{e9}llo");
+    assert_eq!(second.offset, 0..6);
+    assert_eq!(first.ref_count(), 2);
+    assert!(Rc::ptr_eq(&first.string, &second.string));
+}
+
+#[test]
+fn test_common_prefix_suffix_shared() {
+    let string: ImString<Threadsafe> = ImString::from("prefix-suffix");
+    let prefix = string.common_prefix("pre");
+    let suffix = string.common_suffix("fix");
+    assert!(Arc::ptr_eq(&prefix.string, &string.string));
+    assert!(Arc::ptr_eq(&suffix.string, &string.string));
+}
+
+#[test]
+fn test_full_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello world");
+    let full = string.slice(3..8).full();
+    assert_eq!(full, "hello world");
+    assert!(Arc::ptr_eq(&full.string, &string.string));
+}
+
+#[test]
+fn test_byte_offset_in_clone() {
+    let root: ImString<Threadsafe> = ImString::from("hello world");
+    let world = root.slice(6..);
+    assert_eq!(world.byte_offset_in(&root.clone()), Some(6..11));
+    let cloned: ImString<Cloned<String>> = ImString::from("hello world");
+    assert_eq!(cloned.slice(6..).byte_offset_in(&cloned.clone()), None);
+}
+
+#[test]
+fn test_byte_offset_in_slices() {
+    let root: ImString<Threadsafe> = ImString::from("fn main() {}");
+    let name = root.slice(3..7);
+    let signature = root.slice(3..9);
+    let body = root.slice(10..);
+    assert_eq!(name.byte_offset_in(&root), Some(3..7));
+    assert_eq!(name.byte_offset_in(&signature), Some(0..4));
+    assert_eq!(signature.byte_offset_in(&name), None);
+    assert_eq!(body.byte_offset_in(&signature), None);
+    assert_eq!(root.byte_offset_in(&name), None);
+}
+
+#[test]
+fn test_take_shared() {
+    let mut string: ImString<Threadsafe> = ImString::from("hello world");
+    let original = string.clone();
+    let hello = string.take(5);
+    string.advance(1);
+    assert_eq!(string, "world");
+    assert!(Arc::ptr_eq(&hello.string, &original.string));
+    assert!(Arc::ptr_eq(&string.string, &original.string));
+}
+
+#[test]
+fn test_pad_shared() {
+    let string: ImString<Threadsafe> = ImString::from("wide");
+    assert!(Arc::ptr_eq(
+        &string.pad_start(4, ' ').string,
+        &string.string
+    ));
+    assert!(Arc::ptr_eq(&string.pad_end(2, ' ').string, &string.string));
+    assert!(Arc::ptr_eq(&string.center(0, ' ').string, &string.string));
+    assert!(!Arc::ptr_eq(&string.center(5, ' ').string, &string.string));
+}
+
+#[test]
+fn test_join_single_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello");
+    let joined = ImString::join("-", std::slice::from_ref(&string));
+    assert!(Arc::ptr_eq(&joined.string, &string.string));
+    let joined = ImString::join("-", &[string.clone(), string.clone()]);
+    assert_eq!(joined, "hello-hello");
+    assert!(!Arc::ptr_eq(&joined.string, &string.string));
+}
+
+#[test]
+fn test_from_static() {
+    const STRING: &str = "hello w\u{f6}rld";
+    let string = ImString::from_static(STRING);
+    assert_eq!(string, STRING);
+    assert_eq!(string.as_ptr(), STRING.as_ptr());
+    assert_eq!(string.offset, 0..STRING.len());
+    let world = string.slice(6..);
+    assert_eq!(world, "w\u{f6}rld");
+    assert_eq!(world.as_ptr(), STRING[6..].as_ptr());
+    assert_eq!(string.clone().as_ptr(), STRING.as_ptr());
+    let words: Vec<_> = string.split(" ").collect();
+    assert_eq!(words, ["hello", "w\u{f6}rld"]);
+    assert_eq!(words[1].as_ptr(), world.as_ptr());
+    assert!(string.is_unique());
+    assert_eq!(string.ref_count(), 1);
+    assert_eq!(string.capacity(), STRING.len());
+    assert_eq!(string.heap_size(), 0);
+    assert_eq!(world.shared_heap_size(), 0);
+}
+
+#[test]
+fn test_from_static_modify() {
+    const STRING: &str = "hello";
+    let mut string = ImString::from_static(STRING);
+    let original = string.clone();
+    string.push_str(" world");
+    assert_eq!(string, "hello world");
+    assert_ne!(string.as_ptr(), STRING.as_ptr());
+    assert!(string.is_unique());
+    string.push('!');
+    assert_eq!(string, "hello world!");
+    assert_eq!(original, STRING);
+    assert_eq!(original.as_ptr(), STRING.as_ptr());
+    let mut truncated = original.clone();
+    truncated.truncate(2);
+    assert_eq!(truncated, "he");
+    assert_eq!(truncated.as_ptr(), STRING.as_ptr());
+    assert_eq!(original.into_std_string(), STRING);
+}
+
+#[test]
+fn test_owned_iterators_send() {
+    let string: ImString<Threadsafe> = ImString::from("h\u{e9}llo");
+    let chars = string.chars_owned();
+    let bytes = string.bytes_owned();
+    drop(string);
+    let handle = std::thread::spawn(move || (chars.count(), bytes.count()));
+    assert_eq!(handle.join().unwrap(), (5, 6));
+}
+
+#[test]
+fn test_inline_storage() {
+    let mut string: ImString<Inline> = ImString::from("short");
+    assert!(string.string.is_inline());
+    assert_eq!(string.heap_size(), 0);
+    assert_eq!(string.capacity(), 22);
+    let slice = string.slice(1..4);
+    assert_eq!(slice, "hor");
+    assert!(slice.string.is_inline());
+    string.push_str(" and now a lot longer");
+    assert_eq!(string, "short and now a lot longer");
+    assert!(!string.string.is_inline());
+    assert_eq!(string.clone().as_ptr(), string.as_ptr());
+    let long: ImString<Inline> = ImString::from("a string which is too long to be inline");
+    assert!(!long.string.is_inline());
+    assert_eq!(long.slice(2..8), "string");
+    assert!(long.slice(..5).compact().string.is_inline());
+    let mut empty: ImString<Inline> = ImString::new();
+    empty.make_ascii_uppercase();
+    assert!(empty.string.is_inline());
+    assert_eq!(empty, "");
+}
+
+#[test]
+fn test_ensure_unique_capacity_exact() {
+    let mut string: ImString<Threadsafe> = ImString::from("hello world");
+    let clone = string.clone();
+    string.ensure_unique_capacity(1);
+    assert_eq!(string.capacity(), 12);
+    assert!(!Arc::ptr_eq(&string.string, &clone.string));
+    let mut slice = clone.slice(6..);
+    drop(clone);
+    slice.ensure_unique_capacity(3);
+    assert_eq!(slice.raw_offset(), 0..5);
+    assert_eq!(slice.capacity(), 8);
+}
+
+#[test]
+fn test_shrink_offset_to_fit_shared() {
+    let document: ImString<Threadsafe> = ImString::from("hello, world!");
+    let mut string = document.slice(7..12);
+    string.shrink_offset_to_fit();
+    assert!(Arc::ptr_eq(&document.string, &string.string));
+    assert_eq!(string.raw_offset(), 7..12);
+    assert_eq!(document, "hello, world!");
+}
+
+#[test]
+fn test_replace_range_in_place() {
+    let mut string: ImString<Threadsafe> = ImString::with_capacity(32);
+    string.push_str("hello world");
+    let pointer = string.as_ptr();
+    string.replace_range(6.., "there");
+    assert_eq!(string, "hello there");
+    assert_eq!(string.as_ptr(), pointer);
+    let clone = string.clone();
+    string.replace_range(..5, "hi");
+    assert_eq!(string, "hi there");
+    assert_eq!(clone, "hello there");
+    assert!(!Arc::ptr_eq(&string.string, &clone.string));
+}
+
+#[test]
+fn test_insert_end_shared() {
+    let mut string: ImString<Threadsafe> = ImString::with_capacity(16);
+    string.push_str("hello");
+    let clone = string.clone();
+    string.insert_str(5, " world");
+    assert_eq!(string, "hello world");
+    assert_eq!(clone, "hello");
+    assert!(!Arc::ptr_eq(&string.string, &clone.string));
+    let mut prefix = string.slice(..5);
+    drop(string);
+    prefix.insert(5, '!');
+    assert_eq!(prefix, "hello!");
+}
+
+#[test]
+#[should_panic]
+fn test_truncate_split_char() {
+    let mut string: ImString<Threadsafe> = ImString::from("\u{e9}t\u{e9}");
+    string.truncate(4);
+}
+
+#[test]
+fn test_extend_from_within_in_place() {
+    let mut string: ImString<Threadsafe> = ImString::with_capacity(32);
+    string.push_str("ab");
+    let pointer = string.as_ptr();
+    for _ in 0..3 {
+        string.extend_from_within(..);
+    }
+    assert_eq!(string, "abababababababab");
+    assert_eq!(string.as_ptr(), pointer);
+}
+
+#[test]
+#[should_panic]
+fn test_extend_from_within_char_boundary() {
+    let mut string: ImString<Threadsafe> = ImString::from("h\u{e9}llo");
+    string.extend_from_within(2..);
+}
+
+#[test]
+#[should_panic]
+fn test_extend_from_within_out_of_bounds() {
+    let mut string: ImString<Threadsafe> = ImString::from("hello");
+    string.extend_from_within(3..6);
+}
+
+#[test]
+fn test_to_ascii_lowercase_identifier() {
+    let string: ImString<Threadsafe> = ImString::from("user_id");
+    let lower = string.to_ascii_lowercase();
+    assert_eq!(lower.as_ptr(), string.as_ptr());
+    assert_eq!(string.ref_count(), 2);
+    let string: ImString<Threadsafe> = ImString::from("userId");
+    let lower = string.to_ascii_lowercase();
+    assert_eq!(lower, "userid");
+    assert_ne!(lower.as_ptr(), string.as_ptr());
+    assert_eq!(string.ref_count(), 1);
+}
+
+#[test]
+fn test_ascii_case_shared() {
+    let string: ImString<Threadsafe> = ImString::from("user_id");
+    assert!(Arc::ptr_eq(
+        &string.ascii_lowercase().string,
+        &string.string
+    ));
+    let upper = string.ascii_uppercase();
+    assert_eq!(upper, "USER_ID");
+    assert!(!Arc::ptr_eq(&upper.string, &string.string));
+    assert!(Arc::ptr_eq(&upper.ascii_uppercase().string, &upper.string));
+    let lower = upper.ascii_lowercase();
+    assert_eq!(lower, "user_id");
+    assert!(!Arc::ptr_eq(&lower.string, &upper.string));
+}
+
+#[test]
+fn test_byte_key_lookup() {
+    use std::collections::HashMap;
+
+    let mut map: HashMap<ByteKey<crate::ImString>, u32> = HashMap::new();
+    map.insert(ByteKey(ImString::from("content-type")), 1);
+    map.insert(ByteKey(ImString::from("h\u{e9}llo")), 2);
+    let key: &[u8] = b"content-type";
+    assert_eq!(map.get(key), Some(&1));
+    let key: &[u8] = b"h\xc3\xa9llo";
+    assert_eq!(map.get(key), Some(&2));
+    let key: &[u8] = b"Content-Type";
+    assert_eq!(map.get(key), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_try_from_path_invalid() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    let path = PathBuf::from(OsString::from_vec(b"/tmp/\xFFfile".to_vec()));
+    let error = ImString::<Threadsafe>::try_from(path.as_path()).unwrap_err();
+    assert_eq!(error, path.as_path());
+    let error = ImString::<Threadsafe>::try_from(path.clone()).unwrap_err();
+    assert_eq!(error, path);
+}
+
+#[test]
+fn test_chunks_shared() {
+    let string: ImString<Threadsafe> = ImString::from("h\u{e9}llo w\u{f6}rld");
+    for chunk in string.chunks_bytes(3).chain(string.chunks_chars(3)) {
+        assert!(Arc::ptr_eq(&chunk.string, &string.string));
     }
 }
+
+#[test]
+#[should_panic]
+fn test_chunks_bytes_zero() {
+    let string: ImString<Threadsafe> = ImString::from("hello");
+    string.chunks_bytes(0);
+}
+
+#[test]
+fn test_rotate_identity_shared() {
+    let string: ImString<Threadsafe> = ImString::from("hello world");
+    let slice = string.slice(6..);
+    assert!(Arc::ptr_eq(&slice.rotate(0).string, &string.string));
+    assert!(Arc::ptr_eq(&slice.rotate(5).string, &string.string));
+    let rotated = slice.rotate(2);
+    assert_eq!(rotated, "rldwo");
+    assert!(!Arc::ptr_eq(&rotated.string, &string.string));
+    assert_eq!(rotated.offset, 0..5);
+}
+
+#[test]
+#[should_panic]
+fn test_rotate_char_boundary() {
+    let string: ImString<Threadsafe> = ImString::from("h\u{e9}llo");
+    string.rotate(2);
+}
+
+#[test]
+fn test_split_first_last_char_shared() {
+    let string: ImString<Threadsafe> = ImString::from("\u{e9}t\u{e9}");
+    let (_, rest) = string.split_first_char().unwrap();
+    assert!(Arc::ptr_eq(&rest.string, &string.string));
+    assert_eq!(rest.offset, 2..5);
+    let (_, rest) = rest.split_last_char().unwrap();
+    assert!(Arc::ptr_eq(&rest.string, &string.string));
+    assert_eq!(rest.offset, 2..3);
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "bytes")]
+#[macro_use]
+mod common;
+
+use bytes::Bytes;
+use imstr::data::Data;
+use imstr::string::{ImString, Threadsafe};
+
+tests! {
+    #[test]
+    fn bytes_round_trip<S: Data<String>>() {
+        for input in ["", "hello, world", "h\u{e9}llo w\u{f6}rld \u{1f600}"] {
+            let string: ImString<S> = ImString::from(input);
+            let bytes = string.to_bytes();
+            assert_eq!(bytes, input.as_bytes());
+            let string: ImString<S> = ImString::try_from_bytes(bytes).unwrap();
+            assert_eq!(string, input);
+        }
+    }
+
+    #[test]
+    fn bytes_slice<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("gr\u{fc}\u{df}e, welt");
+        assert_eq!(string.slice(..7).to_bytes(), "gr\u{fc}\u{df}e");
+        let bytes = Bytes::from("gr\u{fc}\u{df}e, welt").slice(9..);
+        let string: ImString<S> = ImString::try_from_bytes(bytes).unwrap();
+        assert_eq!(string, "welt");
+    }
+
+    #[test]
+    fn bytes_invalid<S: Data<String>>() {
+        let bytes = Bytes::from_static(b"hello \xF0\x90\x80world");
+        let error = ImString::<S>::try_from_bytes(bytes.clone()).unwrap_err();
+        assert_eq!(error.utf8_error().valid_up_to(), 6);
+        assert_eq!(error.as_bytes(), bytes);
+        let error = ImString::<S>::try_from_bytes(bytes.slice(..7)).unwrap_err();
+        assert_eq!(error.utf8_error().error_len(), None);
+    }
+}
+
+#[test]
+fn bytes_reuses_allocation() {
+    let bytes = Bytes::from(String::from("hello"));
+    let pointer = bytes.as_ptr();
+    let string: ImString<Threadsafe> = ImString::try_from_bytes(bytes).unwrap();
+    assert_eq!(string.as_ptr(), pointer);
+}
@@ -0,0 +1,17 @@
+/// Runs each test function once for every storage type of `ImString`.
+macro_rules! tests {
+    ($(#[test] fn $name:ident <S: Data<String>>() $body:block)*) => {
+        $(
+            #[test]
+            fn $name() {
+                fn $name<S: Data<String>>() $body
+                $name::<::imstr::string::Threadsafe>();
+                $name::<::imstr::string::Local>();
+                $name::<::imstr::data::Cloned<String>>();
+                $name::<Box<String>>();
+                $name::<::imstr::data::Static>();
+                $name::<::imstr::data::Inline>();
+            }
+        )*
+    };
+}
@@ -0,0 +1,134 @@
+//! Fuzz-style tests checking the invariants of `try_slice` and the `Arbitrary` implementation.
+//!
+//! These generate random strings and ranges using the `arbitrary` crate from a deterministic
+//! stream of pseudo-random bytes. Run them with:
+//!
+//! ```sh
+//! cargo test --features arbitrary --test fuzz
+//! ```
+//!
+//! The number of iterations can be raised by setting `IMSTR_FUZZ_ITERATIONS`.
+#![cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Result, Unstructured};
+use imstr::error::SliceError;
+use imstr::ImString;
+use std::ops::Bound;
+
+type Bounds = (Bound<usize>, Bound<usize>);
+
+/// Simple xorshift generator, used to produce input bytes for `arbitrary`.
+struct Random(u64);
+
+impl Random {
+    fn bytes(&mut self, length: usize) -> Vec<u8> {
+        (0..length)
+            .map(|_| {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0 as u8
+            })
+            .collect()
+    }
+}
+
+fn iterations() -> usize {
+    std::env::var("IMSTR_FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(10_000)
+}
+
+fn bound(input: &mut Unstructured<'_>, length: usize) -> Result<Bound<usize>> {
+    let value = input.int_in_range(0..=length + 2)?;
+    Ok(match input.int_in_range(0..=2)? {
+        0 => Bound::Included(value),
+        1 => Bound::Excluded(value),
+        _ => Bound::Unbounded,
+    })
+}
+
+/// Generates a string, possibly pre-sliced, and a range to slice it with.
+fn input(input: &mut Unstructured<'_>) -> Result<(ImString, Bounds)> {
+    let string = ImString::arbitrary(input)?;
+    let start = bound(input, string.len())?;
+    let end = bound(input, string.len())?;
+    Ok((string, (start, end)))
+}
+
+/// Determines the expected byte range, or the reason why the range is invalid.
+fn expected(string: &str, range: Bounds) -> std::result::Result<(usize, usize), SliceError> {
+    let start = match range.0 {
+        Bound::Included(value) => value,
+        Bound::Excluded(value) => value + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.1 {
+        Bound::Included(value) => value + 1,
+        Bound::Excluded(value) => value,
+        Bound::Unbounded => string.len(),
+    };
+    if start > string.len() {
+        Err(SliceError::StartOutOfBounds)
+    } else if end < start {
+        Err(SliceError::EndBeforeStart)
+    } else if end > string.len() {
+        Err(SliceError::EndOutOfBounds)
+    } else if !string.is_char_boundary(start) {
+        Err(SliceError::StartNotAligned)
+    } else if !string.is_char_boundary(end) {
+        Err(SliceError::EndNotAligned)
+    } else {
+        Ok((start, end))
+    }
+}
+
+#[test]
+fn fuzz_try_slice() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for _ in 0..iterations() {
+        let bytes = random.bytes(64);
+        let mut unstructured = Unstructured::new(&bytes);
+        let (string, range) = match input(&mut unstructured) {
+            Ok(input) => input,
+            Err(_) => continue,
+        };
+
+        let result = string.try_slice(range);
+        match (&result, expected(&string, range)) {
+            (Ok(slice), Ok((start, end))) => {
+                assert_eq!(slice, &string[start..end]);
+                let reference = string.try_slice_ref(slice.as_bytes()).unwrap();
+                assert_eq!(reference.raw_offset(), slice.raw_offset());
+            }
+            (Err(error), Err(expected)) => assert_eq!(*error, expected),
+            (_, expected) => {
+                panic!("slicing {string:?} with {range:?}: got {result:?}, expected {expected:?}")
+            }
+        }
+    }
+}
+
+#[test]
+fn fuzz_arbitrary() {
+    let mut random = Random(0x9e37_79b9_7f4a_7c15);
+    let mut sliced = 0;
+    for _ in 0..iterations() {
+        let bytes = random.bytes(64);
+        let mut unstructured = Unstructured::new(&bytes);
+        let string = match ImString::arbitrary(&mut unstructured) {
+            Ok(string) => string,
+            Err(_) => continue,
+        };
+        let backing = string.raw_string();
+        let offset = string.raw_offset();
+        assert!(offset.start <= offset.end && offset.end <= backing.len());
+        assert!(backing.is_char_boundary(offset.start));
+        assert!(backing.is_char_boundary(offset.end));
+        if offset != (0..backing.len()) {
+            sliced += 1;
+        }
+        assert_eq!(string.as_str(), &backing[offset]);
+    }
+    assert!(sliced > 0);
+}
@@ -0,0 +1,103 @@
+#[path = "common/alloc.rs"]
+mod alloc;
+
+use alloc::allocations;
+use imstr::data::Inline;
+use imstr::string::ImString;
+
+#[test]
+fn short_strings_do_not_allocate() {
+    let ((string, slice, clone), count) = allocations(|| {
+        let string: ImString<Inline> = ImString::from("h\u{e9}llo, world");
+        let slice = string.slice(8..);
+        (string.clone(), slice, string)
+    });
+    assert_eq!(count, 0);
+    assert_eq!(string, "h\u{e9}llo, world");
+    assert_eq!(slice, "world");
+    assert_eq!(clone.as_str(), string.as_str());
+}
+
+#[test]
+fn long_strings_are_shared() {
+    let (string, count) = allocations(|| {
+        let string: ImString<Inline> = ImString::from("this string does not fit inline");
+        string
+    });
+    assert!(count > 0);
+    let ((clone, slice), count) = allocations(|| (string.clone(), string.slice(5..11)));
+    assert_eq!(count, 0);
+    assert_eq!(clone.as_ptr(), string.as_ptr());
+    assert_eq!(slice, "string");
+}
+
+#[test]
+fn modify_inline_strings() {
+    let mut string: ImString<Inline> = ImString::from("small");
+    let clone = string.clone();
+    string.push_str(" string");
+    assert_eq!(string.heap_size(), 0);
+    string.insert(0, '<');
+    assert_eq!(string.heap_size(), 0);
+    string.push('>');
+    assert_eq!(string.heap_size(), 0);
+    assert_eq!(string, "<small string>");
+    assert_eq!(clone, "small");
+    string.truncate(6);
+    assert_eq!(string, "<small");
+    assert_eq!(string.heap_size(), 0);
+    let mut string: ImString<Inline<8>> = ImString::from("eight ch");
+    string.push_str("ars");
+    assert_eq!(string, "eight chars");
+    assert_eq!(string.slice(6..), "chars");
+}
+
+#[test]
+fn append_inline_strings_in_place() {
+    let mut string: ImString<Inline> = ImString::from("ab");
+    let ((), count) = allocations(|| {
+        string.push_str("c");
+        string.push('d');
+        string.insert_str(4, "e");
+        string += "f";
+    });
+    assert_eq!(count, 0);
+    assert_eq!(string, "abcdef");
+    assert_eq!(string.heap_size(), 0);
+}
+
+#[test]
+fn empty_strings_do_not_allocate() {
+    let ((new, default, empty), count) = allocations(|| {
+        let new: ImString<Inline> = ImString::new();
+        let default: ImString<Inline> = ImString::default();
+        let empty: ImString<Inline> = ImString::from(String::new());
+        (new, default, empty)
+    });
+    assert_eq!(count, 0);
+    assert!(new.is_empty() && default.is_empty() && empty.is_empty());
+    assert_eq!(new.heap_size(), 0);
+}
+
+#[test]
+fn truncate_inline_strings_in_place() {
+    let mut string: ImString<Inline> = ImString::from("h\u{e9}llo, world");
+    let ((), count) = allocations(|| string.truncate(6));
+    assert_eq!(count, 0);
+    assert_eq!(string, "h\u{e9}llo");
+    assert_eq!(string.heap_size(), 0);
+}
+
+#[test]
+fn shrink_inline_strings_does_not_allocate() {
+    let mut string: ImString<Inline> = ImString::from("h\u{e9}llo, world").slice(8..);
+    let clone = string.clone();
+    let ((), count) = allocations(|| {
+        string.shrink_to_fit();
+        string.shrink_to(64);
+    });
+    assert_eq!(count, 0);
+    assert_eq!(string, "world");
+    assert_eq!(clone, "world");
+    assert_eq!(string.heap_size(), 0);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "nom")]
+#[macro_use]
+mod common;
+
+use imstr::data::Data;
+use imstr::string::{ImString, Threadsafe};
+use nom::bytes::complete::{tag, take_until, take_while1};
+use nom::character::complete::{alpha1, char, digit1, space0};
+use nom::combinator::{opt, recognize};
+use nom::multi::separated_list0;
+use nom::sequence::{delimited, pair, separated_pair};
+use nom::IResult;
+
+fn key_value<S: Data<String>>(
+    input: ImString<S>,
+) -> IResult<ImString<S>, (ImString<S>, ImString<S>)> {
+    separated_pair(
+        alpha1,
+        delimited(space0, char(':'), space0),
+        take_while1(|c: char| c != ';'),
+    )(input)
+}
+
+fn number<S: Data<String>>(input: ImString<S>) -> IResult<ImString<S>, ImString<S>> {
+    recognize(pair(opt(char('-')), digit1))(input)
+}
+
+tests! {
+    #[test]
+    fn parse_key_value<S: Data<String>>() {
+        let input: ImString<S> = ImString::from("name: value;rest");
+        let (rest, (key, value)) = key_value(input.clone()).unwrap();
+        assert_eq!(key, "name");
+        assert_eq!(value, "value");
+        assert_eq!(rest, ";rest");
+        assert_eq!(value.raw_offset(), 6..11);
+    }
+
+    #[test]
+    fn parse_list<S: Data<String>>() {
+        let input: ImString<S> = ImString::from("[1,-23,456]");
+        let mut list = delimited(tag("["), separated_list0(char(','), number), tag("]"));
+        let (rest, numbers): (ImString<S>, Vec<ImString<S>>) = list(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(numbers, ["1", "-23", "456"]);
+    }
+
+    #[test]
+    fn parse_take_until<S: Data<String>>() {
+        let input: ImString<S> = ImString::from("h\u{e9}llo world");
+        let result: IResult<_, _> = take_until("world")(input.clone());
+        let (rest, taken) = result.unwrap();
+        assert_eq!(taken, "h\u{e9}llo ");
+        assert_eq!(rest, "world");
+        let result: IResult<_, ImString<S>> = take_until("missing")(input);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_error<S: Data<String>>() {
+        let input: ImString<S> = ImString::from("123: value");
+        let error = key_value(input).unwrap_err();
+        match error {
+            nom::Err::Error(error) => assert_eq!(error.input, "123: value"),
+            other => panic!("unexpected error {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn parse_shares_data() {
+    let input: ImString<Threadsafe> = ImString::from("key:value");
+    let (_, (key, value)) = key_value(input.clone()).unwrap();
+    assert_eq!(key.as_ptr(), input.as_ptr());
+    assert_eq!(value.as_ptr(), input[4..].as_ptr());
+    assert_eq!(input.ref_count(), 3);
+}
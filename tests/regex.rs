@@ -0,0 +1,62 @@
+#![cfg(feature = "regex")]
+#[macro_use]
+mod common;
+
+use imstr::data::Data;
+use imstr::string::{ImString, Threadsafe};
+use regex::Regex;
+
+tests! {
+    #[test]
+    fn regex_find<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("abc 123 d\u{e9}f 4567");
+        let regex = Regex::new(r"\d+").unwrap();
+        let found = string.regex_find(&regex).unwrap();
+        assert_eq!(found, "123");
+        assert_eq!(found.raw_offset(), 4..7);
+        let found = string.slice(8..).regex_find(&regex).unwrap();
+        assert_eq!(found, "4567");
+        assert_eq!(found.raw_offset(), 13..17);
+        assert_eq!(string.regex_find(&Regex::new("xyz").unwrap()), None);
+    }
+
+    #[test]
+    fn regex_captures_numbered<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("2023-04-05");
+        let regex = Regex::new(r"(\d{4})-(\d{2})-(\d{2})").unwrap();
+        let captures = string.regex_captures(&regex).unwrap();
+        assert_eq!(captures.len(), 4);
+        let captures: Vec<_> = captures.into_iter().map(Option::unwrap).collect();
+        assert_eq!(captures, ["2023-04-05", "2023", "04", "05"]);
+        assert_eq!(captures[2].raw_offset(), 5..7);
+    }
+
+    #[test]
+    fn regex_captures_named<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("name=value; flag");
+        let regex = Regex::new(r"(?P<key>\w+)(?:=(?P<value>\w+))?$").unwrap();
+        let captures = string.regex_captures(&regex).unwrap();
+        let index = |name| {
+            regex
+                .capture_names()
+                .position(|group| group == Some(name))
+                .unwrap()
+        };
+        assert_eq!(captures[index("key")].as_ref().unwrap(), "flag");
+        assert_eq!(captures[index("value")], None);
+
+        let captures = string.slice(..10).regex_captures(&regex).unwrap();
+        assert_eq!(captures[index("key")].as_ref().unwrap(), "name");
+        assert_eq!(captures[index("value")].as_ref().unwrap(), "value");
+        assert_eq!(string.regex_captures(&Regex::new(r"\d").unwrap()), None);
+    }
+}
+
+#[test]
+fn regex_captures_share_data() {
+    let string: ImString<Threadsafe> = ImString::from("a=b");
+    let regex = Regex::new(r"(\w)=(\w)").unwrap();
+    let captures = string.regex_captures(&regex).unwrap();
+    assert_eq!(string.ref_count(), 4);
+    assert_eq!(captures[2].as_ref().unwrap().as_ptr(), string[2..].as_ptr());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "serde")]
+#[macro_use]
+mod common;
+
+use imstr::data::Data;
+use imstr::string::ImString;
+use serde_test::{assert_de_tokens, assert_tokens, Token};
+use std::collections::BTreeMap;
+
+const STRINGS: &[&str] = &[
+    "",
+    "hello",
+    "\u{e4}\u{fc}\u{f6}\u{f8}\u{3a9}",
+    "\u{1f600}\u{1f603}",
+];
+
+tests! {
+    #[test]
+    fn round_trip_json<S: Data<String>>() {
+        for input in STRINGS {
+            let string: ImString<S> = ImString::from(*input);
+            let json = serde_json::to_string(&string).unwrap();
+            assert_eq!(json, serde_json::to_string(input).unwrap());
+            let output: ImString<S> = serde_json::from_str(&json).unwrap();
+            assert_eq!(output, string);
+        }
+    }
+
+    #[test]
+    fn round_trip_sliced<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("hello world");
+        let world = string.slice(6..);
+        let json = serde_json::to_string(&world).unwrap();
+        assert_eq!(json, "\"world\"");
+        let output: ImString<S> = serde_json::from_str(&json).unwrap();
+        assert_eq!(output, "world");
+        assert_eq!(output.raw_offset(), 0..5);
+    }
+
+    #[test]
+    fn tokens<S: Data<String>>() {
+        for input in STRINGS {
+            let string: ImString<S> = ImString::from(*input);
+            assert_tokens(&string, &[Token::Str(input)]);
+            assert_de_tokens(&string, &[Token::BorrowedStr(input)]);
+            assert_de_tokens(&string, &[Token::String(input)]);
+        }
+    }
+
+    #[test]
+    fn map_keys_and_values<S: Data<String>>() {
+        let input = "{\"name\":\"value\"}";
+        let map: BTreeMap<ImString<S>, ImString<S>> = serde_json::from_str(input).unwrap();
+        assert_eq!(map.get("name").unwrap(), "value");
+        assert_eq!(serde_json::to_string(&map).unwrap(), input);
+    }
+}
@@ -0,0 +1,77 @@
+#[path = "common/alloc.rs"]
+mod alloc;
+
+use alloc::allocations;
+use imstr::data::Static;
+use imstr::string::ImString;
+
+#[test]
+fn from_static_does_not_allocate() {
+    let (string, count) = allocations(|| ImString::from_static("hello, world"));
+    assert_eq!(count, 0);
+    assert_eq!(string, "hello, world");
+}
+
+#[test]
+fn slice_static_does_not_allocate() {
+    let string = ImString::from_static("hello, world");
+    let ((hello, world, clone), count) = allocations(|| {
+        let hello = string.slice(..5);
+        let world = string.try_slice(7..).unwrap();
+        (hello, world, string.clone())
+    });
+    assert_eq!(count, 0);
+    assert_eq!(hello, "hello");
+    assert_eq!(world, "world");
+    assert_eq!(clone, string);
+
+    let ((trimmed, (left, right)), count) = allocations(|| {
+        let trimmed = string.trim_end_matches("d");
+        (trimmed, string.split_once(", ").unwrap())
+    });
+    assert_eq!(count, 0);
+    assert_eq!(trimmed, "hello, worl");
+    assert_eq!(left, "hello");
+    assert_eq!(right, "world");
+}
+
+#[test]
+fn modify_static_allocates() {
+    let mut string: ImString<Static> = ImString::from_static("hello");
+    let ((), count) = allocations(|| string.push_str(", world"));
+    assert!(count > 0);
+    assert_eq!(string, "hello, world");
+}
+
+#[test]
+fn heap_size_does_not_allocate() {
+    let string = ImString::from_static("hello world");
+    let ((heap_size, capacity), count) = allocations(|| (string.heap_size(), string.capacity()));
+    assert_eq!(count, 0);
+    assert_eq!(heap_size, 0);
+    assert_eq!(capacity, 11);
+}
+
+#[test]
+fn shrink_static_does_not_allocate() {
+    let mut string = ImString::from_static("hello, world").slice(..5);
+    let ((), count) = allocations(|| {
+        string.shrink_to_fit();
+        string.shrink_to(64);
+    });
+    assert_eq!(count, 0);
+    assert_eq!(string, "hello");
+    assert_eq!(string.heap_size(), 0);
+}
+
+#[test]
+fn ascii_case_allocates_only_when_changed() {
+    let string = ImString::from_static("content-type");
+    let (lower, count) = allocations(|| string.ascii_lowercase());
+    assert_eq!(count, 0);
+    assert_eq!(lower, "content-type");
+    let string = ImString::from_static("Content-Type");
+    let (lower, count) = allocations(|| string.ascii_lowercase());
+    assert!(count > 0);
+    assert_eq!(lower, "content-type");
+}
@@ -1,4 +1,11 @@
 // Taken from https://github.com/rust-lang/rust/blob/master/library/alloc/tests/string.rs
+// The tests are kept as close to the original as possible, so some lints are allowed for them.
+#![allow(
+    unused_imports,
+    clippy::char_lit_as_u8,
+    clippy::into_iter_on_ref,
+    clippy::option_as_ref_deref
+)]
 use imstr::ImString;
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -285,84 +292,83 @@ fn test_lines() {
     }
 }
 
-/*
-pub trait IntoCow<'a, B: ?Sized>
-where
-    B: ToOwned,
-{
-    fn into_cow(self) -> Cow<'a, B>;
+#[test]
+#[should_panic]
+fn test_split_at_mid_char() {
+    let string = ImString::from("\u{1f600}");
+    let _ = string.split_at(1);
 }
 
-impl<'a> IntoCow<'a, str> for ImString {
-    fn into_cow(self) -> Cow<'a, str> {
-        Cow::Owned(self)
-    }
+#[test]
+#[should_panic]
+fn test_split_at_past_end() {
+    let string = ImString::from("abc");
+    let _ = string.split_at(4);
 }
 
-impl<'a> IntoCow<'a, str> for &'a str {
-    fn into_cow(self) -> Cow<'a, str> {
-        Cow::Borrowed(self)
-    }
+#[test]
+fn test_pop() {
+    let mut data = ImString::from("ประเทศไทย中华b¢€𤭢");
+    assert_eq!(data.pop().unwrap(), '𤭢'); // 4 bytes
+    assert_eq!(data.pop().unwrap(), '€'); // 3 bytes
+    assert_eq!(data.pop().unwrap(), '¢'); // 2 bytes
+    assert_eq!(data.pop().unwrap(), 'b'); // 1 bytes
+    assert_eq!(data.pop().unwrap(), '华');
+    assert_eq!(data, "ประเทศไทย中");
 }
 
-
-
-
 #[test]
-fn test_from_utf8_lossy() {
-    let xs = b"hello";
-    let ys: Cow<'_, str> = "hello".into_cow();
-    assert_eq!(ImString::from_utf8_lossy(xs), ys);
-
-    let xs = "ศไทย中华Việt Nam".as_bytes();
-    let ys: Cow<'_, str> = "ศไทย中华Việt Nam".into_cow();
-    assert_eq!(ImString::from_utf8_lossy(xs), ys);
-
-    let xs = b"Hello\xC2 There\xFF Goodbye";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("Hello\u{FFFD} There\u{FFFD} Goodbye").into_cow()
-    );
-
-    let xs = b"Hello\xC0\x80 There\xE6\x83 Goodbye";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("Hello\u{FFFD}\u{FFFD} There\u{FFFD} Goodbye").into_cow()
-    );
-
-    let xs = b"\xF5foo\xF5\x80bar";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("\u{FFFD}foo\u{FFFD}\u{FFFD}bar").into_cow()
-    );
+fn remove() {
+    let mut s = ImString::from("ศไทย中华Việt Nam; foobar");
+    assert_eq!(s.remove(0), 'ศ');
+    assert_eq!(s.len(), 33);
+    assert_eq!(s, "ไทย中华Việt Nam; foobar");
+    assert_eq!(s.remove(17), 'ệ');
+    assert_eq!(s, "ไทย中华Vit Nam; foobar");
+}
 
-    let xs = b"\xF1foo\xF1\x80bar\xF1\x80\x80baz";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("\u{FFFD}foo\u{FFFD}bar\u{FFFD}baz").into_cow()
-    );
+#[test]
+#[should_panic]
+fn remove_bad() {
+    ImString::from("ศ").remove(1);
+}
 
-    let xs = b"\xF4foo\xF4\x80bar\xF4\xBFbaz";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("\u{FFFD}foo\u{FFFD}bar\u{FFFD}\u{FFFD}baz").into_cow()
-    );
+#[test]
+#[should_panic]
+fn remove_end() {
+    ImString::from("abc").remove(3);
+}
 
-    let xs = b"\xF0\x80\x80\x80foo\xF0\x90\x80\x80bar";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}foo\u{10000}bar").into_cow()
-    );
+#[test]
+fn test_drain() {
+    let mut s = ImString::from("αβγ");
+    assert_eq!(s.drain(2..4).collect::<ImString>(), "β");
+    assert_eq!(s, "αγ");
 
-    // surrogates
-    let xs = b"\xED\xA0\x80foo\xED\xBF\xBFbar";
-    assert_eq!(
-        ImString::from_utf8_lossy(xs),
-        ImString::from("\u{FFFD}\u{FFFD}\u{FFFD}foo\u{FFFD}\u{FFFD}\u{FFFD}bar").into_cow()
-    );
+    let mut t = ImString::from("abcd");
+    t.drain(..0);
+    assert_eq!(t, "abcd");
+    t.drain(..1);
+    assert_eq!(t, "bcd");
+    t.drain(3..);
+    assert_eq!(t, "bcd");
+    t.drain(..);
+    assert_eq!(t, "");
 }
 
+#[test]
+#[should_panic]
+fn test_drain_start_overflow() {
+    let mut s = ImString::from("abc");
+    s.drain((Excluded(usize::MAX), Included(0)));
+}
 
+#[test]
+#[should_panic]
+fn test_drain_end_overflow() {
+    let mut s = ImString::from("abc");
+    s.drain((Included(0), Included(usize::MAX)));
+}
 
 #[test]
 fn test_from_utf16() {
@@ -438,12 +444,21 @@ fn test_utf16_invalid() {
 fn test_from_utf16_lossy() {
     // completely positive cases tested above.
     // lead + eof
-    assert_eq!(ImString::from_utf16_lossy(&[0xD800]), ImString::from("\u{FFFD}"));
+    assert_eq!(
+        ImString::from_utf16_lossy(&[0xD800]),
+        ImString::from("\u{FFFD}")
+    );
     // lead + lead
-    assert_eq!(ImString::from_utf16_lossy(&[0xD800, 0xD800]), ImString::from("\u{FFFD}\u{FFFD}"));
+    assert_eq!(
+        ImString::from_utf16_lossy(&[0xD800, 0xD800]),
+        ImString::from("\u{FFFD}\u{FFFD}")
+    );
 
     // isolated trail
-    assert_eq!(ImString::from_utf16_lossy(&[0x0061, 0xDC00]), ImString::from("a\u{FFFD}"));
+    assert_eq!(
+        ImString::from_utf16_lossy(&[0x0061, 0xDC00]),
+        ImString::from("a\u{FFFD}")
+    );
 
     // general
     assert_eq!(
@@ -453,171 +468,19 @@ fn test_from_utf16_lossy() {
 }
 
 #[test]
-fn test_push_bytes() {
-    let mut s = ImString::from("ABC");
-    unsafe {
-        let mv = s.as_mut_vec();
-        mv.extend_from_slice(&[b'D']);
-    }
-    assert_eq!(s, "ABCD");
-}
-
-#[test]
-fn test_pop() {
-    let mut data = ImString::from("ประเทศไทย中华b¢€𤭢");
-    assert_eq!(data.pop().unwrap(), '𤭢'); // 4 bytes
-    assert_eq!(data.pop().unwrap(), '€'); // 3 bytes
-    assert_eq!(data.pop().unwrap(), '¢'); // 2 bytes
-    assert_eq!(data.pop().unwrap(), 'b'); // 1 bytes
-    assert_eq!(data.pop().unwrap(), '华');
-    assert_eq!(data, "ประเทศไทย中");
-}
-
-#[test]
-fn test_str_truncate() {
-    let mut s = ImString::from("12345");
-    s.truncate(5);
-    assert_eq!(s, "12345");
-    s.truncate(3);
-    assert_eq!(s, "123");
-    s.truncate(0);
-    assert_eq!(s, "");
-
-    let mut s = ImString::from("12345");
-    let p = s.as_ptr();
-    s.truncate(3);
-    s.push_str("6");
-    let p_ = s.as_ptr();
-    assert_eq!(p_, p);
-}
-
-#[test]
-fn test_str_truncate_invalid_len() {
-    let mut s = ImString::from("12345");
-    s.truncate(6);
-    assert_eq!(s, "12345");
-}
-
-#[test]
-#[should_panic]
-fn test_str_truncate_split_codepoint() {
-    let mut s = ImString::from("\u{FC}"); // ü
-    s.truncate(1);
-}
-
-#[test]
-fn remove() {
-    let mut s = "ศไทย中华Việt Nam; foobar".to_string();
-    assert_eq!(s.remove(0), 'ศ');
-    assert_eq!(s.len(), 33);
-    assert_eq!(s, "ไทย中华Việt Nam; foobar");
-    assert_eq!(s.remove(17), 'ệ');
-    assert_eq!(s, "ไทย中华Vit Nam; foobar");
-}
-
-#[test]
-#[should_panic]
-fn remove_bad() {
-    "ศ".to_string().remove(1);
-}
-
-#[test]
-fn test_retain() {
-    let mut s = ImString::from("α_β_γ");
-
-    s.retain(|_| true);
-    assert_eq!(s, "α_β_γ");
-
-    s.retain(|c| c != '_');
-    assert_eq!(s, "αβγ");
-
-    s.retain(|c| c != 'β');
-    assert_eq!(s, "αγ");
-
-    s.retain(|c| c == 'α');
-    assert_eq!(s, "α");
-
-    s.retain(|_| false);
-    assert_eq!(s, "");
-
-    let mut s = ImString::from("0è0");
-    let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-        let mut count = 0;
-        s.retain(|_| {
-            count += 1;
-            match count {
-                1 => false,
-                2 => true,
-                _ => panic!(),
-            }
-        });
-    }));
-    assert!(std::str::from_utf8(s.as_bytes()).is_ok());
-}
-
-#[test]
-fn test_slicing() {
-    let s = "foobar".to_string();
-    assert_eq!("foobar", &s[..]);
-    assert_eq!("foo", &s[..3]);
-    assert_eq!("bar", &s[3..]);
-    assert_eq!("oob", &s[1..4]);
-}
-
-#[test]
-fn test_simple_types() {
-    assert_eq!(1.to_string(), "1");
-    assert_eq!((-1).to_string(), "-1");
-    assert_eq!(200.to_string(), "200");
-    assert_eq!(2.to_string(), "2");
-    assert_eq!(true.to_string(), "true");
-    assert_eq!(false.to_string(), "false");
-    assert_eq!(("hi".to_string()).to_string(), "hi");
-}
-
-#[test]
-fn test_vectors() {
-    let x: Vec<i32> = vec![];
-    assert_eq!(format!("{x:?}"), "[]");
-    assert_eq!(format!("{:?}", vec![1]), "[1]");
-    assert_eq!(format!("{:?}", vec![1, 2, 3]), "[1, 2, 3]");
-    assert!(format!("{:?}", vec![vec![], vec![1], vec![1, 1]]) == "[[], [1], [1, 1]]");
-}
-
-#[test]
-fn test_drain() {
-    let mut s = ImString::from("αβγ");
-    assert_eq!(s.drain(2..4).collect::<ImString>(), "β");
-    assert_eq!(s, "αγ");
-
-    let mut t = ImString::from("abcd");
-    t.drain(..0);
-    assert_eq!(t, "abcd");
-    t.drain(..1);
-    assert_eq!(t, "bcd");
-    t.drain(3..);
-    assert_eq!(t, "bcd");
-    t.drain(..);
-    assert_eq!(t, "");
-}
-
-#[test]
-#[should_panic]
-fn test_drain_start_overflow() {
-    let mut s = ImString::from("abc");
-    s.drain((Excluded(usize::MAX), Included(0)));
-}
-
-#[test]
-#[should_panic]
-fn test_drain_end_overflow() {
-    let mut s = ImString::from("abc");
-    s.drain((Included(0), Included(usize::MAX)));
+fn test_cmp_literals() {
+    let string = ImString::from("hello");
+    assert!(string < "zzz");
+    assert!(string > "abc");
+    assert!("abc" < string);
+    let other = String::from("hellp");
+    assert!(other > string);
+    assert!("hello" == string);
 }
 
 #[test]
 fn test_replace_range() {
-    let mut s = "Hello, world!".to_owned();
+    let mut s = ImString::from("Hello, world!");
     s.replace_range(7..12, "世界");
     assert_eq!(s, "Hello, 世界!");
 }
@@ -625,7 +488,7 @@ fn test_replace_range() {
 #[test]
 #[should_panic]
 fn test_replace_range_char_boundary() {
-    let mut s = "Hello, 世界!".to_owned();
+    let mut s = ImString::from("Hello, 世界!");
     s.replace_range(..8, "");
 }
 
@@ -726,6 +589,190 @@ fn test_replace_range_evil_end_bound() {
     assert_eq!(Ok(""), str::from_utf8(s.as_bytes()));
 }
 
+#[test]
+fn test_str_truncate() {
+    let mut s = ImString::from("12345");
+    s.truncate(5);
+    assert_eq!(s, "12345");
+    s.truncate(3);
+    assert_eq!(s, "123");
+    s.truncate(0);
+    assert_eq!(s, "");
+
+    let mut s = ImString::from("12345");
+    let p = s.as_ptr();
+    s.truncate(3);
+    s.push_str("6");
+    let p_ = s.as_ptr();
+    assert_eq!(p_, p);
+}
+
+#[test]
+fn test_str_truncate_invalid_len() {
+    let mut s = ImString::from("12345");
+    s.truncate(6);
+    assert_eq!(s, "12345");
+}
+
+#[test]
+#[should_panic]
+fn test_str_truncate_split_codepoint() {
+    let mut s = ImString::from("\u{FC}"); // ü
+    s.truncate(1);
+}
+
+/*
+pub trait IntoCow<'a, B: ?Sized>
+where
+    B: ToOwned,
+{
+    fn into_cow(self) -> Cow<'a, B>;
+}
+
+impl<'a> IntoCow<'a, str> for ImString {
+    fn into_cow(self) -> Cow<'a, str> {
+        Cow::Owned(self)
+    }
+}
+
+impl<'a> IntoCow<'a, str> for &'a str {
+    fn into_cow(self) -> Cow<'a, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+
+
+
+#[test]
+fn test_from_utf8_lossy() {
+    let xs = b"hello";
+    let ys: Cow<'_, str> = "hello".into_cow();
+    assert_eq!(ImString::from_utf8_lossy(xs), ys);
+
+    let xs = "ศไทย中华Việt Nam".as_bytes();
+    let ys: Cow<'_, str> = "ศไทย中华Việt Nam".into_cow();
+    assert_eq!(ImString::from_utf8_lossy(xs), ys);
+
+    let xs = b"Hello\xC2 There\xFF Goodbye";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("Hello\u{FFFD} There\u{FFFD} Goodbye").into_cow()
+    );
+
+    let xs = b"Hello\xC0\x80 There\xE6\x83 Goodbye";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("Hello\u{FFFD}\u{FFFD} There\u{FFFD} Goodbye").into_cow()
+    );
+
+    let xs = b"\xF5foo\xF5\x80bar";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("\u{FFFD}foo\u{FFFD}\u{FFFD}bar").into_cow()
+    );
+
+    let xs = b"\xF1foo\xF1\x80bar\xF1\x80\x80baz";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("\u{FFFD}foo\u{FFFD}bar\u{FFFD}baz").into_cow()
+    );
+
+    let xs = b"\xF4foo\xF4\x80bar\xF4\xBFbaz";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("\u{FFFD}foo\u{FFFD}bar\u{FFFD}\u{FFFD}baz").into_cow()
+    );
+
+    let xs = b"\xF0\x80\x80\x80foo\xF0\x90\x80\x80bar";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}foo\u{10000}bar").into_cow()
+    );
+
+    // surrogates
+    let xs = b"\xED\xA0\x80foo\xED\xBF\xBFbar";
+    assert_eq!(
+        ImString::from_utf8_lossy(xs),
+        ImString::from("\u{FFFD}\u{FFFD}\u{FFFD}foo\u{FFFD}\u{FFFD}\u{FFFD}bar").into_cow()
+    );
+}
+
+
+
+#[test]
+fn test_push_bytes() {
+    let mut s = ImString::from("ABC");
+    unsafe {
+        let mv = s.as_mut_vec();
+        mv.extend_from_slice(&[b'D']);
+    }
+    assert_eq!(s, "ABCD");
+}
+
+#[test]
+fn test_retain() {
+    let mut s = ImString::from("α_β_γ");
+
+    s.retain(|_| true);
+    assert_eq!(s, "α_β_γ");
+
+    s.retain(|c| c != '_');
+    assert_eq!(s, "αβγ");
+
+    s.retain(|c| c != 'β');
+    assert_eq!(s, "αγ");
+
+    s.retain(|c| c == 'α');
+    assert_eq!(s, "α");
+
+    s.retain(|_| false);
+    assert_eq!(s, "");
+
+    let mut s = ImString::from("0è0");
+    let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let mut count = 0;
+        s.retain(|_| {
+            count += 1;
+            match count {
+                1 => false,
+                2 => true,
+                _ => panic!(),
+            }
+        });
+    }));
+    assert!(std::str::from_utf8(s.as_bytes()).is_ok());
+}
+
+#[test]
+fn test_slicing() {
+    let s = "foobar".to_string();
+    assert_eq!("foobar", &s[..]);
+    assert_eq!("foo", &s[..3]);
+    assert_eq!("bar", &s[3..]);
+    assert_eq!("oob", &s[1..4]);
+}
+
+#[test]
+fn test_simple_types() {
+    assert_eq!(1.to_string(), "1");
+    assert_eq!((-1).to_string(), "-1");
+    assert_eq!(200.to_string(), "200");
+    assert_eq!(2.to_string(), "2");
+    assert_eq!(true.to_string(), "true");
+    assert_eq!(false.to_string(), "false");
+    assert_eq!(("hi".to_string()).to_string(), "hi");
+}
+
+#[test]
+fn test_vectors() {
+    let x: Vec<i32> = vec![];
+    assert_eq!(format!("{x:?}"), "[]");
+    assert_eq!(format!("{:?}", vec![1]), "[1]");
+    assert_eq!(format!("{:?}", vec![1, 2, 3]), "[1, 2, 3]");
+    assert!(format!("{:?}", vec![vec![], vec![1], vec![1, 1]]) == "[[], [1], [1, 1]]");
+}
+
 #[test]
 fn test_into_boxed_str() {
     let xs = ImString::from("hello my name is bob");
@@ -0,0 +1,84 @@
+#![cfg(feature = "unicode")]
+#[macro_use]
+mod common;
+
+use imstr::data::Data;
+use imstr::error::SliceError;
+use imstr::string::{ImString, Threadsafe};
+use unicode_segmentation::UnicodeSegmentation;
+
+const STRINGS: &[&str] = &[
+    "",
+    "hello",
+    "o\u{308}",
+    "a\u{310}e\u{301}o\u{308}\u{332}",
+    "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}",
+    "\u{1f1e9}\u{1f1ea}\u{1f1eb}\u{1f1f7}",
+    "line\r\nbreak",
+];
+
+tests! {
+    #[test]
+    fn graphemes_match_unicode_segmentation<S: Data<String>>() {
+        for input in STRINGS {
+            let string: ImString<S> = ImString::from(*input);
+            for extended in [true, false] {
+                let graphemes: Vec<ImString<S>> = string.graphemes(extended).collect();
+                let expected: Vec<&str> = input.graphemes(extended).collect();
+                assert_eq!(graphemes, expected);
+                let reversed: Vec<ImString<S>> = string.graphemes(extended).rev().collect();
+                let expected: Vec<&str> = input.graphemes(extended).rev().collect();
+                assert_eq!(reversed, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn graphemes_combining_diacritics<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("no\u{308}el");
+        let graphemes: Vec<ImString<S>> = string.graphemes(true).collect();
+        assert_eq!(graphemes, ["n", "o\u{308}", "e", "l"]);
+    }
+
+    #[test]
+    fn graphemes_zwj_sequence<S: Data<String>>() {
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}";
+        let string: ImString<S> = ImString::from(format!("a{family}b"));
+        let graphemes: Vec<ImString<S>> = string.graphemes(true).collect();
+        assert_eq!(graphemes, ["a", family, "b"]);
+    }
+
+    #[test]
+    fn graphemes_sliced<S: Data<String>>() {
+        let string: ImString<S> = ImString::from("xo\u{308}y\u{301}z");
+        let slice = string.slice(1..7);
+        let graphemes: Vec<ImString<S>> = slice.graphemes(true).collect();
+        assert_eq!(graphemes, ["o\u{308}", "y\u{301}"]);
+    }
+}
+
+#[test]
+fn graphemes_share_data() {
+    let string: ImString<Threadsafe> = ImString::from("o\u{308}a\u{301}");
+    let graphemes: Vec<_> = string.graphemes(true).collect();
+    assert_eq!(string.ref_count(), 1 + graphemes.len());
+    assert_eq!(graphemes[1].as_ptr(), string[3..].as_ptr());
+}
+
+#[test]
+fn slice_graphemes() {
+    let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}";
+    let string: ImString<Threadsafe> = ImString::from(format!("no\u{308}{family}!"));
+    assert_eq!(string.slice_graphemes(1..2), "o\u{308}");
+    assert_eq!(string.slice_graphemes(2..=2), family);
+    assert_eq!(string.slice_graphemes(3..), "!");
+    assert_eq!(string.slice_graphemes(4..), "");
+    assert_eq!(
+        string.try_slice_graphemes(5..),
+        Err(SliceError::StartOutOfBounds)
+    );
+    assert_eq!(
+        string.try_slice_graphemes(..5),
+        Err(SliceError::EndOutOfBounds)
+    );
+}